@@ -10,15 +10,17 @@ use std::{
 };
 
 const BLOCK_SIZE: usize = 128;
+// how many code changes can be waiting for the audio thread
+const QUEUE_SIZE: usize = 64;
 
+/// The control side of the engine; the audio thread owns the processor and never locks.
 #[derive(Resource)]
 pub struct GlicolEngine {
-    pub engine: Arc<Mutex<glicol::Engine<BLOCK_SIZE>>>,
+    pub engine: Mutex<glicol::EngineHandle<BLOCK_SIZE>>,
 }
 
 impl GlicolEngine {
     pub fn new() -> Self {
-        let engine = Arc::new(Mutex::new(glicol::Engine::<BLOCK_SIZE>::new()));
         let host = cpal::default_host();
         let device = host
             .default_output_device()
@@ -26,14 +28,20 @@ impl GlicolEngine {
         let config = device.default_output_config().unwrap();
         info!("Default output config: {:?}", config);
 
-        let engine_clone = engine.clone();
+        let mut engine = glicol::Engine::<BLOCK_SIZE>::with_channels(config.channels() as usize);
+        engine.set_sr(config.sample_rate().0 as usize);
+        engine.set_master(glicol::MasterConfig::safe());
+        engine.livecoding = false;
+        let (handle, processor) = engine.split(QUEUE_SIZE);
 
         thread::spawn(move || match config.sample_format() {
-            cpal::SampleFormat::F32 => run_audio::<f32>(&device, &config.into(), engine_clone),
+            cpal::SampleFormat::F32 => run_audio::<f32>(&device, &config.into(), processor),
             sample_format => panic!("Unsupported sample format '{sample_format}'"),
         });
 
-        Self { engine }
+        Self {
+            engine: Mutex::new(handle),
+        }
     }
 
     pub fn update_with_code(&self, code: &str) {
@@ -42,25 +50,91 @@ impl GlicolEngine {
             error!("Failed to update Glicol code: {}", e);
         }
     }
+
+    /// Keeps a whole program to switch to later; see [`glicol::Engine::add_scene`].
+    pub fn add_scene(&self, name: &str, code: &str) {
+        if let Err(e) = self.engine.lock().add_scene(name, code) {
+            error!("Failed to add Glicol scene {}: {}", name, e);
+        }
+    }
+
+    /// Switches scenes at the next `quantize` boundary, e.g. when the game state changes.
+    pub fn switch_scene(&self, name: &str, quantize: glicol::Quantize, crossfade_ms: f32) {
+        if let Err(e) = self
+            .engine
+            .lock()
+            .switch_scene(name, quantize, crossfade_ms)
+        {
+            error!("Failed to switch to Glicol scene {}: {}", name, e);
+        }
+    }
+
+    /// Sends `msg` on an exact sample, e.g. `glicol::Message::Trigger(1.)` to an `sp` for a
+    /// one-shot sound effect; see [`glicol::Engine::schedule`].
+    pub fn schedule(
+        &self,
+        at: glicol::EventTime,
+        chain: &str,
+        node: glicol::NodeSelector<'_>,
+        msg: glicol::Message,
+    ) {
+        if let Err(e) = self.engine.lock().schedule(at, chain, node, msg) {
+            error!("Failed to schedule a message to {}: {}", chain, e);
+        }
+    }
+
+    /// Goes back to the program sent before the current one.
+    pub fn undo(&self) {
+        if let Err(e) = self.engine.lock().undo() {
+            error!("Failed to undo Glicol code: {}", e);
+        }
+    }
+
+    pub fn redo(&self) {
+        if let Err(e) = self.engine.lock().redo() {
+            error!("Failed to redo Glicol code: {}", e);
+        }
+    }
+
+    /// Adds a node written in Rust to the language; see [`glicol::Engine::register_node`].
+    pub fn register_node<F>(&self, name: &str, params: &'static [glicol_synth::ParamInfo], build: F)
+    where
+        F: Fn(&[glicol::NodeArg], usize, f32) -> glicol::GlicolNodeData<BLOCK_SIZE>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.engine.lock().register_node(name, params, build);
+    }
+
+    /// Levels and recent samples of `chain`, for audio-reactive systems.
+    pub fn tap(&self, chain: &str) -> Option<glicol::Tap> {
+        match self.engine.lock().tap(chain) {
+            Ok(tap) => Some(tap),
+            Err(e) => {
+                error!("Failed to tap {}: {}", chain, e);
+                None
+            }
+        }
+    }
+
+    /// The chains, nodes and connections of the program, for inspectors and visualizers.
+    pub fn describe(&self) -> glicol::GraphDescription {
+        self.engine.lock().describe()
+    }
 }
 
 fn run_audio<T>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
-    engine: Arc<Mutex<glicol::Engine<BLOCK_SIZE>>>,
+    mut processor: glicol::EngineProcessor<BLOCK_SIZE>,
 ) -> Result<(), anyhow::Error>
 where
     T: SizedSample + FromSample<f32>,
 {
-    let sr = config.sample_rate.0 as usize;
-    let channels = 2_usize; //config.channels as usize;
-
-    engine.lock().set_sr(sr);
-    engine.lock().livecoding = false;
-
-    let engine_clone = engine.clone();
+    let channels = config.channels as usize;
 
-    let mut prev_block: [glicol_synth::Buffer<BLOCK_SIZE>; 2] = [glicol_synth::Buffer::SILENT; 2];
+    let mut prev_block = vec![glicol_synth::Buffer::<BLOCK_SIZE>::SILENT; channels];
 
     let ptr = prev_block.as_mut_ptr();
     let prev_block_ptr = Arc::new(AtomicPtr::<glicol_synth::Buffer<BLOCK_SIZE>>::new(ptr));
@@ -95,8 +169,7 @@ where
 
             prev_block_pos = BLOCK_SIZE;
             while writes < block_step {
-                let mut e = engine_clone.lock();
-                let block = e.next_block(vec![]);
+                let block = processor.next_block(&[]);
 
                 if writes + BLOCK_SIZE <= block_step {
                     for i in 0..BLOCK_SIZE {
@@ -4,5 +4,5 @@ fn main() {
     engine
         .update_with_code(r#"o: constsig 42 >> pan 0.9"#)
         .unwrap();
-    println!("next block {:?}", engine.next_block(vec![]));
+    println!("next block {:?}", engine.next_block(&[]));
 }
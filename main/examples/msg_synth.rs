@@ -4,5 +4,5 @@ fn main() {
     engine
         .update_with_code(r#"o: msgsynth \saw 0.01 0.1"#)
         .unwrap();
-    println!("next block {:?}", engine.next_block(vec![]));
+    println!("next block {:?}", engine.next_block(&[]));
 }
@@ -13,7 +13,7 @@ fn main() {
     let mut n = 0;
 
     for _ in 0..(220500 / 128) {
-        let buf = engine.next_block(vec![]);
+        let buf = engine.next_block(&[]);
         for i in 0..128 {
             x.push(n);
             n += 1;
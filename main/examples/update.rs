@@ -60,7 +60,7 @@ fn main() {
     o: balance ~t1 ~t2"#,
         )
         .unwrap();
-    println!(" engine.next_block() 0 {:?}", engine.next_block(vec![]));
+    println!(" engine.next_block() 0 {:?}", engine.next_block(&[]));
     engine
         .update_with_code(
             r#"
@@ -70,5 +70,5 @@ fn main() {
     o: balance ~t1 ~t3"#,
         )
         .unwrap();
-    println!(" engine.next_block() 1 {:?}", engine.next_block(vec![]));
+    println!(" engine.next_block() 1 {:?}", engine.next_block(&[]));
 }
@@ -0,0 +1,356 @@
+//! What the running program looks like, for tools that draw or inspect it.
+
+use glicol_parser::nodes::{self, Ast, Component};
+use hashbrown::HashMap;
+
+use crate::{
+    diff::{matching_chains, Slot},
+    registry::NodeRegistry,
+    util::{feedback_reference, references},
+    Engine, GraphUpdate, ParamOverride, ParamValue,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Every chain of the program with its nodes, and every connection between them.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GraphDescription {
+    /// Sorted by name. Chains that live in the engine but not in the code, like `~input`,
+    /// are included.
+    pub chains: Vec<ChainDescription>,
+    pub edges: Vec<EdgeDescription>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChainDescription {
+    pub name: String,
+    pub nodes: Vec<NodeDescription>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeDescription {
+    /// The node type as written in the code, like `"lpf"`.
+    pub name: String,
+    pub params: Vec<ParamDescription>,
+    /// The chains the node reads from, as written in the code.
+    pub references: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParamDescription {
+    pub name: String,
+    pub index: u8,
+    pub value: ParamSetting,
+}
+
+/// What drives a parameter.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParamSetting {
+    /// The value from the code, or the one set since with `set_param` or `send_msg`.
+    Value(ParamValue),
+    /// Another chain, like `sin ~mod`.
+    Reference(String),
+    /// A pattern, like `lpf "300@0.0 600@0.5"(1) 1.0`.
+    Pattern,
+}
+
+/// A node, by its chain and position in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeId {
+    pub chain: String,
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EdgeKind {
+    /// From a node to the next one in its chain.
+    Chain,
+    /// From the last node of a chain to a node that reads it.
+    Reference,
+    /// From the last node of an audible chain to the master output.
+    Output,
+    /// From the last node of a chain to an `fb` node, which reads it one block late.
+    Feedback,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EdgeDescription {
+    pub from: NodeId,
+    /// `None` for the master output.
+    pub to: Option<NodeId>,
+    pub kind: EdgeKind,
+}
+
+/// The parameter values set with `set_param` or `send_msg` since the code set them, by chain
+/// and position.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ParamValues(HashMap<(String, usize), Vec<(u8, ParamValue)>>);
+
+impl ParamValues {
+    pub fn insert(&mut self, chain: &str, position: usize, index: u8, value: ParamValue) {
+        let values = self.0.entry((chain.to_string(), position)).or_default();
+        values.retain(|(i, _)| *i != index);
+        values.push((index, value));
+    }
+
+    fn get(&self, chain: &str, position: usize, index: u8) -> Option<&ParamValue> {
+        let values = self.0.get(&(chain.to_string(), position))?;
+        values
+            .iter()
+            .find(|(i, _)| *i == index)
+            .map(|(_, value)| value)
+    }
+
+    /// Moves the values along with the nodes `update` keeps, and forgets the ones its code
+    /// sets again.
+    pub fn follow<const N: usize>(&mut self, update: &GraphUpdate<N>) {
+        if self.0.is_empty() {
+            return;
+        }
+        let mut old = std::mem::take(&mut self.0);
+        for (chain, slots) in &update.layout {
+            for (i, slot) in slots.iter().enumerate() {
+                let Slot::Keep(j) = slot else { continue };
+                let Some(mut values) = old.remove(&(chain.clone(), *j)) else {
+                    continue;
+                };
+                for (_, _, msg) in update
+                    .messages
+                    .iter()
+                    .filter(|(c, position, _)| c == chain && *position == i)
+                {
+                    if let Some((index, _)) = ParamValue::from_message(msg) {
+                        values.retain(|(k, _)| *k != index);
+                    }
+                }
+                self.0.insert((chain.clone(), i), values);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Every changed parameter, sorted by chain, position and index.
+    pub fn overrides(&self) -> Vec<ParamOverride> {
+        let mut overrides: Vec<ParamOverride> = self
+            .0
+            .iter()
+            .flat_map(|((chain, position), values)| {
+                values.iter().map(|(index, value)| ParamOverride {
+                    chain: chain.clone(),
+                    position: *position,
+                    index: *index,
+                    value: value.clone(),
+                })
+            })
+            .collect();
+        overrides
+            .sort_by(|a, b| (&a.chain, a.position, a.index).cmp(&(&b.chain, b.position, b.index)));
+        overrides
+    }
+}
+
+// the parameters as the code sets them
+fn code_settings(component: &Component<'_>) -> Vec<(u8, ParamSetting)> {
+    use nodes::NumberOrRef::{Number, Ref};
+    use ParamSetting::{Reference, Value};
+
+    let number = |index: u8, value: f32| (index, Value(ParamValue::Number(value)));
+    let number_or_ref = |index: u8, param: &nodes::NumberOrRef<&str>| match param {
+        Number(value) => number(index, *value),
+        Ref(chain) => (index, Reference(chain.to_string())),
+    };
+    let symbol = |index: u8, value: &str| (index, Value(ParamValue::Symbol(value.to_string())));
+
+    match component {
+        Component::Sin(c) => vec![number_or_ref(0, &c.param)],
+        Component::Saw(c) => vec![number_or_ref(0, &c.param)],
+        Component::Squ(c) => vec![number_or_ref(0, &c.param)],
+        Component::Tri(c) => vec![number_or_ref(0, &c.param)],
+        Component::Imp(c) => vec![number_or_ref(0, &c.param)],
+        Component::Mul(c) => vec![number_or_ref(0, &c.param)],
+        Component::Add(c) => vec![number_or_ref(0, &c.param)],
+        Component::Pan(c) => vec![number_or_ref(0, &c.param)],
+        Component::Onepole(c) => vec![number_or_ref(0, &c.param)],
+        Component::Delayms(c) => vec![number_or_ref(0, &c.param)],
+        Component::Bd(c) => vec![number_or_ref(0, &c.param)],
+        Component::Sn(c) => vec![number_or_ref(0, &c.param)],
+        Component::Hh(c) => vec![number_or_ref(0, &c.param)],
+        Component::Delayn(c) => vec![match &c.param {
+            nodes::UsizeOrRef::Usize(value) => number(0, *value as f32),
+            nodes::UsizeOrRef::Ref(chain) => (0, Reference(chain.to_string())),
+        }],
+        Component::ConstSig(c) => vec![number(0, c.value)],
+        Component::Speed(c) => vec![number(0, c.speed)],
+        Component::Plate(c) => vec![number(0, c.mix)],
+        Component::Noise(c) => vec![number(0, c.seed as f32)],
+        Component::Choose(c) => vec![(0, Value(ParamValue::NumberList(c.choices.clone())))],
+        Component::EnvPerc(c) => vec![number(0, c.attack), number(1, c.decay)],
+        Component::SawSynth(c) => vec![number(0, c.attack), number(1, c.decay)],
+        Component::SquSynth(c) => vec![number(0, c.attack), number(1, c.decay)],
+        Component::TriSynth(c) => vec![number(0, c.attack), number(1, c.decay)],
+        Component::Adsr(c) => vec![
+            number(0, c.attack),
+            number(1, c.decay),
+            number(2, c.sustain),
+            number(3, c.release),
+        ],
+        Component::MsgSynth(c) => vec![number(1, c.attack), number(2, c.decay)],
+        Component::PatternSynth(c) => vec![symbol(0, c.symbol)],
+        Component::Rhpf(c) => vec![number_or_ref(0, &c.cutoff), number(1, c.qvalue)],
+        Component::ApfmsGain(c) => vec![number_or_ref(0, &c.delay), number(1, c.gain)],
+        Component::Lpf(c) => vec![
+            match &c.signal {
+                nodes::Signal::Number(value) => number(0, *value),
+                nodes::Signal::Reference(chain) => (0, Reference(chain.to_string())),
+                nodes::Signal::Pattern(_) | nodes::Signal::Event(_) => (0, ParamSetting::Pattern),
+            },
+            number(1, c.qvalue),
+        ],
+        Component::Eval(c) => vec![symbol(0, c.code.code)],
+        Component::Meta(c) => vec![symbol(0, c.code.code)],
+        Component::Custom(c) => (0..)
+            .zip(&c.args)
+            .map(|(index, arg)| match arg {
+                nodes::Argument::Number(value) => number(index, *value),
+                nodes::Argument::Symbol(value) => symbol(index, value),
+                nodes::Argument::Ref(chain) => (index, Reference(chain.to_string())),
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// what the program `ast` runs, with the chains in `fixed` that aren't in the code
+pub(crate) fn describe<const N: usize>(
+    ast: Option<&Ast<'_>>,
+    fixed: &[&str],
+    values: &ParamValues,
+    nodes: &NodeRegistry<N>,
+) -> GraphDescription {
+    let mut chains: Vec<ChainDescription> = ast
+        .iter()
+        .flat_map(|ast| &ast.nodes)
+        .map(|(chain, components)| ChainDescription {
+            name: chain.to_string(),
+            nodes: components
+                .iter()
+                .enumerate()
+                .map(|(position, component)| {
+                    let settings = code_settings(component);
+                    let params = nodes
+                        .params(component)
+                        .iter()
+                        .filter_map(|info| {
+                            let (_, setting) = settings.iter().find(|(i, _)| *i == info.index)?;
+                            let value = match values.get(chain, position, info.index) {
+                                Some(value) if !matches!(setting, ParamSetting::Reference(_)) => {
+                                    ParamSetting::Value(value.clone())
+                                }
+                                _ => setting.clone(),
+                            };
+                            Some(ParamDescription {
+                                name: info.name.to_string(),
+                                index: info.index,
+                                value,
+                            })
+                        })
+                        .collect();
+                    let mut reflist = references(component);
+                    reflist.extend(feedback_reference(component).map(ToString::to_string));
+                    NodeDescription {
+                        name: component.name().to_string(),
+                        params,
+                        references: reflist,
+                    }
+                })
+                .collect(),
+        })
+        .chain(fixed.iter().map(|chain| ChainDescription {
+            name: chain.to_string(),
+            nodes: vec![NodeDescription {
+                name: "pass".to_string(),
+                params: vec![],
+                references: vec![],
+            }],
+        }))
+        .collect();
+    chains.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let last = |chain: &ChainDescription| NodeId {
+        chain: chain.name.clone(),
+        position: chain.nodes.len() - 1,
+    };
+    let mut edges = vec![];
+    for chain in chains.iter().filter(|chain| !chain.nodes.is_empty()) {
+        for (position, node) in chain.nodes.iter().enumerate() {
+            let to = NodeId {
+                chain: chain.name.clone(),
+                position,
+            };
+            let kind = match node.name.as_str() {
+                "fb" => EdgeKind::Feedback,
+                _ => EdgeKind::Reference,
+            };
+            for reference in &node.references {
+                for source in matching_chains(reference, ast, fixed) {
+                    let Some(source) = chains
+                        .iter()
+                        .find(|c| c.name == source && !c.nodes.is_empty())
+                    else {
+                        continue;
+                    };
+                    edges.push(EdgeDescription {
+                        from: last(source),
+                        to: Some(to.clone()),
+                        kind,
+                    });
+                }
+            }
+            if position > 0 {
+                edges.push(EdgeDescription {
+                    from: NodeId {
+                        position: position - 1,
+                        ..to.clone()
+                    },
+                    to: Some(to),
+                    kind: EdgeKind::Chain,
+                });
+            }
+        }
+        if !chain.name.contains('~') {
+            edges.push(EdgeDescription {
+                from: last(chain),
+                to: None,
+                kind: EdgeKind::Output,
+            });
+        }
+    }
+    GraphDescription { chains, edges }
+}
+
+impl<const N: usize> Engine<N> {
+    /// Describes the running program: its chains, their nodes with the current parameter
+    /// values, and every connection. Nodes fading out after an update are left out.
+    pub fn describe(&self) -> GraphDescription {
+        let ast = self.ast.as_ref().map(|ast| ast.get());
+        let fixed: Vec<&str> = self
+            .index_info
+            .keys()
+            .map(String::as_str)
+            .filter(|key| !ast.is_some_and(|ast| ast.nodes.contains_key(key)))
+            .collect();
+        describe(ast, &fixed, &self.param_values, &self.nodes)
+    }
+}
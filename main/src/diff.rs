@@ -0,0 +1,481 @@
+use glicol_parser::{
+    get_ast,
+    nodes::{Ast, Component},
+};
+use glicol_synth::{effect::CrossFade, Message, Node as _, SampleBank};
+use hashbrown::{HashMap, HashSet};
+use petgraph::graph::NodeIndex;
+use yoke::Yoke;
+
+use crate::{
+    registry::NodeRegistry,
+    util::{feedback_reference, makenode, node_seed, references, update_messages},
+    EngineError, GlicolNodeData, YokedAst,
+};
+
+/// Where a node of the new program comes from.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Slot {
+    /// The node at this position of the same chain in the running graph is kept.
+    Keep(usize),
+    /// A freshly built node, taken from `GraphUpdate::nodes`.
+    New(usize),
+}
+
+/// A part of a chain that is faded from its old nodes to its new ones.
+///
+/// The segment sits between two kept nodes of the chain (or its start and end); the old
+/// nodes are kept alive outside of `index_info` until the fade is over.
+pub(crate) struct Fade<const N: usize> {
+    pub chain: String,
+    // position in the new chain of the kept node before and after the segment
+    pub after: Option<usize>,
+    pub before: Option<usize>,
+    pub new_len: usize,
+    pub old_positions: Vec<usize>,
+    pub old: Vec<NodeIndex>,
+    // (index into `old`, source chains)
+    pub old_refs: Vec<(usize, Vec<String>)>,
+    // (index into `old`, source chains) for the old `fb` nodes
+    pub old_feedback: Vec<(usize, Vec<String>)>,
+    pub node: Option<GlicolNodeData<N>>,
+    pub xfade: NodeIndex,
+}
+
+impl<const N: usize> Fade<N> {
+    /// The position in the new chain of the last node that feeds the fade, if any.
+    pub fn new_end(&self) -> Option<usize> {
+        match (self.after, self.new_len) {
+            (after, 0) => after,
+            (None, len) => Some(len - 1),
+            (Some(after), len) => Some(after + len),
+        }
+    }
+}
+
+/// A code change that has been parsed, diffed and built, but not applied yet.
+///
+/// Everything that can fail or allocate heavily happens while the update is prepared,
+/// so applying it only moves nodes in and out of the graph and rewires it. After
+/// [`crate::Engine::apply`] the update holds whatever was replaced (old nodes, the old
+/// AST and index info), so it can be dropped away from the audio thread.
+pub struct GraphUpdate<const N: usize> {
+    pub(crate) ast: Option<YokedAst>,
+    pub(crate) layout: Vec<(String, Vec<Slot>)>,
+    pub(crate) nodes: Vec<Option<GlicolNodeData<N>>>,
+    // (chain, position in the new chain, source chains)
+    pub(crate) refs: Vec<(String, usize, Vec<String>)>,
+    // (chain, position in the new chain, source chains) for the `fb` nodes
+    pub(crate) feedback: Vec<(String, usize, Vec<String>)>,
+    // (chain, position in the new chain, message) for kept nodes whose parameters changed
+    pub(crate) messages: Vec<(String, usize, Message)>,
+    // (chain, position in the old chain)
+    pub(crate) removed: Vec<(String, usize)>,
+    pub(crate) fades: Vec<Fade<N>>,
+    pub(crate) fade_len: usize,
+    // the sample rate and tempo the nodes were built for
+    pub(crate) sr: usize,
+    pub(crate) bpm: f32,
+    // chains like `~input` that live in the graph but not in the code
+    pub(crate) fixed: Vec<String>,
+    pub(crate) index_info: HashMap<String, Vec<NodeIndex>>,
+    pub(crate) garbage: Vec<GlicolNodeData<N>>,
+}
+
+impl<const N: usize> GraphUpdate<N> {
+    /// How many nodes the fades of this update keep in the graph.
+    pub(crate) fn fade_nodes(&self) -> usize {
+        self.fades
+            .iter()
+            .map(|fade| fade.old_positions.len() + 1)
+            .sum()
+    }
+}
+
+/// The state a new program is diffed and built against.
+pub(crate) struct Planner<'a, const N: usize> {
+    pub ast: Option<&'a Ast<'a>>,
+    pub fixed: Vec<&'a str>,
+    pub samples: &'a SampleBank,
+    pub nodes: &'a NodeRegistry<N>,
+    pub sr: usize,
+    pub channels: usize,
+    pub bpm: f32,
+    pub seed: usize,
+    pub crossfade_ms: f32,
+    // nodes of the previous update's fades, removed when this one is applied
+    pub fade_nodes: usize,
+}
+
+impl<const N: usize> Planner<'_, N> {
+    pub fn plan(&self, code: &str) -> Result<GraphUpdate<N>, EngineError> {
+        let ast: YokedAst =
+            Yoke::try_attach_to_cart(code.to_owned().into_boxed_str(), |code| get_ast(code))?;
+        let new_ast = ast.get();
+        let fade_len = (self.crossfade_ms * self.sr as f32 / 1000.) as usize;
+
+        let mut layout = Vec::with_capacity(new_ast.nodes.len());
+        let mut nodes = vec![];
+        let mut refs = vec![];
+        let mut feedback = vec![];
+        let mut messages = vec![];
+        let mut removed = vec![];
+        let mut fades = vec![];
+        let mut index_info = HashMap::with_capacity(new_ast.nodes.len() + self.fixed.len());
+
+        // in a fixed order, so the same code always gets the same node indices
+        let mut new_chains: Vec<_> = new_ast.nodes.iter().collect();
+        new_chains.sort_unstable_by_key(|(chain_name, _)| *chain_name);
+        for (chain_name, new_chain) in new_chains {
+            let old_chain = self.ast.and_then(|ast| ast.nodes.get(chain_name));
+            let mut kept = vec![false; old_chain.map_or(0, Vec::len)];
+            let mut slots = Vec::with_capacity(new_chain.len());
+
+            let matched = match old_chain {
+                Some(old_chain) => self.match_chain(old_chain, new_chain),
+                None => new_chain.iter().map(|_| None).collect(),
+            };
+
+            for (i, (component, matched)) in new_chain.iter().zip(matched).enumerate() {
+                let reflist = match matched {
+                    Some((j, updates)) => {
+                        kept[j] = true;
+                        slots.push(Slot::Keep(j));
+                        messages.extend(
+                            updates
+                                .into_iter()
+                                .map(|msg| (chain_name.to_string(), i, msg)),
+                        );
+                        references(component)
+                    }
+                    None => {
+                        let seed = node_seed(self.seed, chain_name, i);
+                        let (nodedata, reflist) = makenode(
+                            component,
+                            self.samples,
+                            self.nodes,
+                            self.sr,
+                            self.bpm,
+                            seed,
+                            self.channels,
+                        )?;
+                        slots.push(Slot::New(nodes.len()));
+                        nodes.push(Some(nodedata));
+                        reflist
+                    }
+                };
+
+                if !reflist.is_empty() {
+                    refs.push((chain_name.to_string(), i, self.resolve(reflist, new_ast)?));
+                }
+                if let Some(reference) = feedback_reference(component) {
+                    let sources = self.resolve(vec![reference.to_string()], new_ast)?;
+                    feedback.push((chain_name.to_string(), i, sources));
+                }
+            }
+
+            let chain_fades = match old_chain {
+                Some(old_chain) if fade_len > 0 => {
+                    self.chain_fades(chain_name, old_chain, &slots, new_ast, fade_len)
+                }
+                // a whole new chain fades in, if it can be heard
+                None if fade_len > 0 && !chain_name.contains('~') => vec![Fade {
+                    new_len: new_chain.len(),
+                    ..self.fade(chain_name, None, None, fade_len)
+                }],
+                _ => vec![],
+            };
+            removed.extend(
+                kept.iter()
+                    .enumerate()
+                    .filter(|(j, kept)| {
+                        !**kept && !chain_fades.iter().any(|f| f.old_positions.contains(j))
+                    })
+                    .map(|(j, _)| (chain_name.to_string(), j)),
+            );
+            fades.extend(chain_fades);
+            index_info.insert(chain_name.to_string(), Vec::with_capacity(new_chain.len()));
+            layout.push((chain_name.to_string(), slots));
+        }
+
+        if let Some(chains) = find_cycle(&refs) {
+            return Err(EngineError::Cycle(chains));
+        }
+
+        // there are some chains show up in old ast but not in new ast
+        if let Some(old_ast) = self.ast {
+            let mut old_chains: Vec<_> = old_ast.nodes.iter().collect();
+            old_chains.sort_unstable_by_key(|(chain_name, _)| *chain_name);
+            for (chain_name, old_chain) in old_chains {
+                if new_ast.nodes.contains_key(chain_name) {
+                    continue;
+                }
+                if fade_len > 0 && !chain_name.contains('~') {
+                    let mut fade = self.fade(chain_name, None, None, fade_len);
+                    self.add_old_nodes(&mut fade, old_chain, 0..old_chain.len(), new_ast);
+                    fades.push(fade);
+                } else {
+                    removed.extend((0..old_chain.len()).map(|j| (chain_name.to_string(), j)));
+                }
+            }
+        }
+
+        Ok(GraphUpdate {
+            garbage: Vec::with_capacity(removed.len() + self.fade_nodes),
+            fixed: self.fixed.iter().map(ToString::to_string).collect(),
+            ast: Some(ast),
+            layout,
+            nodes,
+            refs,
+            feedback,
+            messages,
+            removed,
+            fades,
+            fade_len,
+            sr: self.sr,
+            bpm: self.bpm,
+            index_info,
+        })
+    }
+
+    // match the nodes of the new chain to the old one by a longest common subsequence, where
+    // an identical component weighs more than one whose node can take the new parameters
+    // with messages, so an unchanged node is never traded for an updated one
+    #[allow(clippy::type_complexity)]
+    fn match_chain(
+        &self,
+        old_chain: &[Component],
+        new_chain: &[Component],
+    ) -> Vec<Option<(usize, Vec<Message>)>> {
+        let mut updates: Vec<Vec<(usize, Option<Vec<Message>>)>> = old_chain
+            .iter()
+            .map(|old| {
+                new_chain
+                    .iter()
+                    .map(|new| match old == new {
+                        true => (2, Some(vec![])),
+                        // changed nodes are faded like replaced ones, not set in place
+                        false if self.crossfade_ms > 0. => (0, None),
+                        false => match update_messages(old, new, self.samples) {
+                            Some(messages) => (1, Some(messages)),
+                            None => (0, None),
+                        },
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // best[j][i] is the best weight for old_chain[j..] and new_chain[i..]
+        let mut best = vec![vec![0; new_chain.len() + 1]; old_chain.len() + 1];
+        for j in (0..old_chain.len()).rev() {
+            for i in (0..new_chain.len()).rev() {
+                let weight = updates[j][i].0;
+                let diagonal = match weight {
+                    0 => 0,
+                    _ => weight + best[j + 1][i + 1],
+                };
+                best[j][i] = diagonal.max(best[j + 1][i]).max(best[j][i + 1]);
+            }
+        }
+
+        let mut matched: Vec<_> = new_chain.iter().map(|_| None).collect();
+        let (mut j, mut i) = (0, 0);
+        while j < old_chain.len() && i < new_chain.len() {
+            let weight = updates[j][i].0;
+            if weight > 0 && best[j][i] == weight + best[j + 1][i + 1] {
+                matched[i] = updates[j][i].1.take().map(|messages| (j, messages));
+                j += 1;
+                i += 1;
+            } else if best[j + 1][i] >= best[j][i + 1] {
+                j += 1;
+            } else {
+                i += 1;
+            }
+        }
+        matched
+    }
+
+    // split a changed chain into segments between the nodes it keeps, and fade each segment
+    // that lost or gained nodes
+    fn chain_fades(
+        &self,
+        chain_name: &str,
+        old_chain: &[Component],
+        slots: &[Slot],
+        new_ast: &Ast,
+        fade_len: usize,
+    ) -> Vec<Fade<N>> {
+        let anchors: Vec<(usize, usize)> = slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| match slot {
+                Slot::Keep(j) => Some((i, *j)),
+                Slot::New(_) => None,
+            })
+            .collect();
+
+        // kept nodes that moved around can't be faded segment by segment
+        if anchors.windows(2).any(|pair| pair[0].1 > pair[1].1) {
+            return vec![];
+        }
+
+        let mut fades = vec![];
+        let mut prev: Option<(usize, usize)> = None;
+        for next in anchors
+            .iter()
+            .map(|anchor| Some(*anchor))
+            .chain(std::iter::once(None))
+        {
+            let new_start = prev.map_or(0, |(i, _)| i + 1);
+            let old_start = prev.map_or(0, |(_, j)| j + 1);
+            let new_end = next.map_or(slots.len(), |(i, _)| i);
+            let old_end = next.map_or(old_chain.len(), |(_, j)| j);
+            if new_end > new_start || old_end > old_start {
+                let mut fade = Fade {
+                    new_len: new_end - new_start,
+                    ..self.fade(
+                        chain_name,
+                        prev.map(|(i, _)| i),
+                        next.map(|(i, _)| i),
+                        fade_len,
+                    )
+                };
+                self.add_old_nodes(&mut fade, old_chain, old_start..old_end, new_ast);
+                fades.push(fade);
+            }
+            prev = next;
+        }
+        fades
+    }
+
+    fn fade(
+        &self,
+        chain_name: &str,
+        after: Option<usize>,
+        before: Option<usize>,
+        fade_len: usize,
+    ) -> Fade<N> {
+        Fade {
+            chain: chain_name.to_string(),
+            after,
+            before,
+            new_len: 0,
+            old_positions: vec![],
+            old: vec![],
+            old_refs: vec![],
+            old_feedback: vec![],
+            node: Some(CrossFade::new(fade_len).to_boxed_nodedata(self.channels)),
+            xfade: NodeIndex::end(),
+        }
+    }
+
+    fn add_old_nodes(
+        &self,
+        fade: &mut Fade<N>,
+        old_chain: &[Component],
+        positions: std::ops::Range<usize>,
+        new_ast: &Ast,
+    ) {
+        for (k, j) in positions.enumerate() {
+            // the old nodes keep reading whatever of their refs still exists
+            let reflist: Vec<String> = references(&old_chain[j])
+                .iter()
+                .flat_map(|refname| self.matches(refname, new_ast))
+                .collect();
+            if !reflist.is_empty() {
+                fade.old_refs.push((k, reflist));
+            }
+            if let Some(reference) = feedback_reference(&old_chain[j]) {
+                fade.old_feedback
+                    .push((k, self.matches(reference, new_ast)));
+            }
+            fade.old_positions.push(j);
+        }
+        fade.old.reserve(fade.old_positions.len());
+    }
+
+    // make sure every ref points to a chain in the new program (or a fixed one like `~input`),
+    // and expand the `~a..` shorthand to all the chains it matches
+    fn resolve(&self, reflist: Vec<String>, new_ast: &Ast) -> Result<Vec<String>, EngineError> {
+        let mut resolved = Vec::with_capacity(reflist.len());
+        for refname in reflist {
+            let matched = self.matches(&refname, new_ast);
+            if matched.is_empty() {
+                return Err(EngineError::NonExistReference(refname));
+            }
+            resolved.extend(matched);
+        }
+        Ok(resolved)
+    }
+
+    fn matches(&self, refname: &str, new_ast: &Ast) -> Vec<String> {
+        matching_chains(refname, Some(new_ast), &self.fixed)
+    }
+}
+
+/// The chains `refname` reads in the program `ast` with the `fixed` chains: itself, or every
+/// chain starting with the prefix of a `~name..` ref, sorted.
+pub(crate) fn matching_chains(refname: &str, ast: Option<&Ast>, fixed: &[&str]) -> Vec<String> {
+    let chains = || {
+        ast.into_iter()
+            .flat_map(|ast| ast.nodes.keys())
+            .chain(fixed.iter())
+    };
+    if refname.contains("..") {
+        let prefix = refname.replace("..", "");
+        let mut matched: Vec<String> = chains()
+            .filter(|key| key.starts_with(&prefix))
+            .map(ToString::to_string)
+            .collect();
+        matched.sort();
+        matched
+    } else if chains().any(|key| *key == refname) {
+        vec![refname.to_string()]
+    } else {
+        vec![]
+    }
+}
+
+/// A loop of chains that read each other through connections, in the order they read each
+/// other. The graph can't run those in any fixed order; only `fb` may close a loop.
+fn find_cycle(refs: &[(String, usize, Vec<String>)]) -> Option<Vec<String>> {
+    let mut reads: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (chain_name, _, sources) in refs {
+        reads
+            .entry(chain_name.as_str())
+            .or_default()
+            .extend(sources.iter().map(String::as_str));
+    }
+    let mut chains: Vec<&str> = reads.keys().copied().collect();
+    chains.sort_unstable();
+
+    // chains known not to lead into a loop
+    let mut done = HashSet::new();
+    let mut path = vec![];
+    chains
+        .into_iter()
+        .find_map(|chain_name| visit(chain_name, &reads, &mut done, &mut path))
+}
+
+fn visit<'a>(
+    chain_name: &'a str,
+    reads: &HashMap<&'a str, Vec<&'a str>>,
+    done: &mut HashSet<&'a str>,
+    path: &mut Vec<&'a str>,
+) -> Option<Vec<String>> {
+    if let Some(start) = path.iter().position(|c| *c == chain_name) {
+        return Some(path[start..].iter().map(ToString::to_string).collect());
+    }
+    if done.contains(chain_name) {
+        return None;
+    }
+    path.push(chain_name);
+    for source in reads.get(chain_name).into_iter().flatten() {
+        if let Some(cycle) = visit(source, reads, done, path) {
+            return Some(cycle);
+        }
+    }
+    path.pop();
+    done.insert(chain_name);
+    None
+}
@@ -7,6 +7,25 @@ pub enum EngineError {
     ParsingError(Box<pest::error::Error<glicol_parser::Rule>>),
     NonExistReference(String),
     NonExistSample(String),
+    NonExistNode(String),
+    NonExistParam(String),
+    // a program version the history doesn't keep
+    NonExistVersion(usize),
+    NonExistScene(String),
+    // a value of the wrong kind for the parameter
+    InvalidParamValue(String),
+    // a node or a form of a parameter the engine can't build yet
+    UnsupportedNode(String),
+    // the crate feature a node needs
+    MissingFeature(&'static str),
+    InvalidPattern(String),
+    // sample data the samplers can't play
+    InvalidSample(String),
+    // a sample file that can't be read or decoded
+    SampleFile(String),
+    // chains that read each other in a loop, in the order they read each other
+    Cycle(Vec<String>),
+    QueueFull,
 }
 
 impl From<Box<Error<Rule>>> for EngineError {
@@ -31,6 +50,31 @@ impl std::fmt::Display for EngineError {
             Self::ParsingError(err) => writeln!(f, "Parsing error: {err}"),
             EngineError::NonExistSample(v) => writeln!(f, "There is no sample named {v}s"),
             EngineError::NonExistReference(v) => writeln!(f, "There is no reference named {v}"),
+            EngineError::NonExistNode(v) => writeln!(f, "There is no node {v}"),
+            EngineError::NonExistParam(v) => writeln!(f, "There is no parameter {v}"),
+            EngineError::NonExistVersion(v) => {
+                writeln!(f, "There is no program version {v} in the history")
+            }
+            EngineError::NonExistScene(v) => writeln!(f, "There is no scene named {v}"),
+            EngineError::InvalidParamValue(v) => writeln!(f, "Invalid parameter value: {v}"),
+            EngineError::UnsupportedNode(v) => {
+                writeln!(f, "{v} is not supported in the engine yet")
+            }
+            EngineError::MissingFeature(v) => {
+                writeln!(f, "The `{v}` feature is required for this node")
+            }
+            EngineError::InvalidPattern(v) => writeln!(f, "Invalid pattern: {v}"),
+            EngineError::InvalidSample(v) => writeln!(f, "Invalid sample {v}"),
+            EngineError::SampleFile(v) => writeln!(f, "Can't load the sample file {v}"),
+            EngineError::Cycle(v) => {
+                let chains: Vec<&str> = v.iter().chain(v.first()).map(String::as_str).collect();
+                writeln!(
+                    f,
+                    "The chains {} read each other in a loop; read one of them with `fb`",
+                    chains.join(" -> ")
+                )
+            }
+            EngineError::QueueFull => writeln!(f, "The audio thread is not taking changes"),
         }
     }
 }
@@ -0,0 +1,157 @@
+use std::collections::VecDeque;
+
+use crate::{Engine, EngineError};
+
+/// The programs sent to an engine, numbered from 0 in the order they were sent.
+/// Only the last [`History::capacity`] are kept.
+#[derive(Debug, Clone)]
+pub struct History {
+    // (version, code), oldest first
+    entries: VecDeque<(usize, Box<str>)>,
+    // where the running program is in `entries`
+    current: Option<usize>,
+    next_version: usize,
+    capacity: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            current: None,
+            next_version: 0,
+            capacity: 64,
+        }
+    }
+}
+
+impl History {
+    /// The version of the running program, or of the last one waiting to be applied.
+    /// `None` once that version is no longer kept.
+    pub fn current(&self) -> Option<usize> {
+        Some(self.entries[self.current?].0)
+    }
+
+    pub fn get(&self, version: usize) -> Option<&str> {
+        let position = self.position(version)?;
+        Some(&self.entries[position].1)
+    }
+
+    /// Every version kept with its code, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &str)> {
+        self.entries
+            .iter()
+            .map(|(version, code)| (*version, &**code))
+    }
+
+    /// How many versions are kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // new code replaces the versions undone before it, like in a text editor
+    pub(crate) fn push(&mut self, code: &str) {
+        if self
+            .current
+            .is_some_and(|current| &*self.entries[current].1 == code)
+        {
+            return;
+        }
+        self.entries
+            .truncate(self.current.map_or(0, |current| current + 1));
+        self.entries
+            .push_back((self.next_version, code.to_owned().into_boxed_str()));
+        self.next_version += 1;
+        self.trim();
+        self.current = Some(self.entries.len() - 1);
+    }
+
+    pub(crate) fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        let excess = self.entries.len().saturating_sub(self.capacity);
+        self.trim();
+        // the running program may have been trimmed itself
+        self.current = self.current.and_then(|current| current.checked_sub(excess));
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.entries.clear();
+        self.current = None;
+    }
+
+    fn trim(&mut self) {
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub(crate) fn position(&self, version: usize) -> Option<usize> {
+        self.entries.iter().position(|(v, _)| *v == version)
+    }
+
+    // the position `steps` away from the current one, if it is kept
+    pub(crate) fn step(&self, steps: isize) -> Option<usize> {
+        self.current?
+            .checked_add_signed(steps)
+            .filter(|position| *position < self.entries.len())
+    }
+
+    pub(crate) fn code_at(&self, position: usize) -> &str {
+        &self.entries[position].1
+    }
+
+    pub(crate) fn set_current(&mut self, position: usize) {
+        self.current = Some(position);
+    }
+}
+
+impl<const N: usize> Engine<N> {
+    /// The programs sent with [`Engine::update_with_code`], for [`Engine::undo`],
+    /// [`Engine::redo`] and [`Engine::revert_to`].
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Keeps the last `capacity` programs, 64 by default.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history.set_capacity(capacity);
+    }
+
+    /// Goes back to the program before the current one, diffing against it like any
+    /// update. Returns the version it went to, or `None` at the oldest one kept.
+    pub fn undo(&mut self) -> Result<Option<usize>, EngineError> {
+        self.step_history(-1)
+    }
+
+    /// Goes forward again after [`Engine::undo`].
+    pub fn redo(&mut self) -> Result<Option<usize>, EngineError> {
+        self.step_history(1)
+    }
+
+    /// Goes to any version still kept. The versions after it stay for [`Engine::redo`]
+    /// until new code is sent.
+    pub fn revert_to(&mut self, version: usize) -> Result<(), EngineError> {
+        let position = self
+            .history
+            .position(version)
+            .ok_or(EngineError::NonExistVersion(version))?;
+        self.go_to(position)
+    }
+
+    fn step_history(&mut self, steps: isize) -> Result<Option<usize>, EngineError> {
+        let Some(position) = self.history.step(steps) else {
+            return Ok(None);
+        };
+        self.go_to(position)?;
+        Ok(self.history.current())
+    }
+
+    // a version may not build anymore, e.g. when a sample it plays was removed
+    fn go_to(&mut self, position: usize) -> Result<(), EngineError> {
+        let update = self.prepare(self.history.code_at(position))?;
+        self.schedule_update(Box::new(update));
+        self.history.set_current(position);
+        self.scenes.set_current(None);
+        Ok(())
+    }
+}
@@ -1,50 +1,108 @@
-// todo: When error, the error info still updates some nodes..
-
+mod describe;
+mod diff;
+mod history;
+mod param;
+mod quantize;
+pub mod realtime;
+mod registry;
+mod render;
+#[cfg(feature = "load-samples")]
+mod sample_file;
+mod scene;
+mod schedule;
+mod snapshot;
+mod tap;
+mod tempo;
+mod transport;
 pub mod util;
-use std::collections::VecDeque;
 
-use util::makenode;
 pub mod error;
-pub use error::{get_error_info, EngineError};
-use glicol_parser::{
-    get_ast,
-    nodes::{Ast, Component, UsizeOrRef},
-    ToInnerOwned as _,
+pub use describe::{
+    ChainDescription, EdgeDescription, EdgeKind, GraphDescription, NodeDescription, NodeId,
+    ParamDescription, ParamSetting,
 };
+pub use diff::GraphUpdate;
+pub use error::{get_error_info, EngineError};
+pub use glicol_synth::{MasterConfig, Message, Sample, SampleBank, Smoothing};
+pub use history::History;
+pub use param::{component_params, NodeSelector, ParamId, ParamValue};
+pub use quantize::Quantize;
+pub use realtime::{EngineHandle, EngineProcessor};
+pub use registry::{NodeArg, NodeRegistry};
+#[cfg(feature = "load-samples")]
+pub use sample_file::decode_sample_file;
+pub use schedule::EventTime;
+pub use snapshot::{ParamOverride, Snapshot};
+pub use tap::Tap;
+pub use tempo::{Tempo, TempoMap};
+pub use transport::{TransportPosition, TransportState};
+
+use describe::ParamValues;
+use diff::{Fade, Planner, Slot};
+use glicol_parser::nodes::Ast;
 use glicol_synth::{
-    AudioContext, AudioContextConfig, BoxedNodeSend, Buffer, GlicolGraph, GlicolPara, Message,
-    NodeData, Pass,
+    AudioContext, AudioContextConfig, BoxedNodeSend, Buffer, Feedback, NodeData, Pass,
 };
 use hashbrown::HashMap;
 use petgraph::graph::NodeIndex;
+use scene::Scenes;
+use schedule::Event;
+use std::collections::VecDeque;
+#[cfg(feature = "use-samples")]
+use std::sync::Arc;
+use tap::TapWriter;
+use util::{node_seed, parse_msg};
 use yoke::Yoke;
 
 pub type GlicolNodeData<const N: usize> = NodeData<BoxedNodeSend<N>, N>;
 type YokedAst = Yoke<Ast<'static>, Box<str>>;
 
-#[derive(Default)]
-struct GraphDiff<'engine, const N: usize> {
-    node_remove_list: Vec<(&'engine str, usize)>,
-    node_update_list: Vec<(&'engine str, usize, Vec<GlicolPara<&'engine str>>)>,
-    refpairlist: Vec<(Vec<String>, &'engine str, usize)>,
-    node_add_list: VecDeque<(&'engine str, usize, GlicolNodeData<N>)>,
-    idx_to_remove: Vec<NodeIndex>,
-}
-
 pub struct Engine<const N: usize> {
     pub context: AudioContext<N>,
     ast: Option<YokedAst>,
     pub index_info: HashMap<String, Vec<NodeIndex>>,
-    pub index_info_backup: HashMap<String, Vec<NodeIndex>>,
-    temp_node_index: Vec<NodeIndex>, // created in the adding process, will be deleted if err
-    pub samples_dict: HashMap<String, (&'static [f32], usize, usize)>,
+    // the `~input` node, looked up once so the audio thread doesn't build its key
+    input: NodeIndex,
+    // the graph is processed from here: it reads the output and every chain an `fb` node
+    // plays, so those run even when nothing else reads them
+    root: NodeIndex,
+    // (chain, position in chain, source chains) for every node that reads other chains
+    refs: Vec<(String, usize, Vec<String>)>,
+    // (chain, position in chain, source chains) for every `fb` node
+    feedback: Vec<(String, usize, Vec<String>)>,
+    // parameters changed since the code set them, for `describe`
+    param_values: ParamValues,
+    history: History,
+    scenes: Scenes,
+    fades: Vec<Fade<N>>,
+    // samples until the fades are done
+    fade_left: usize,
+    crossfade_ms: f32,
+    samples: SampleBank,
+    // the nodes the host added with `register_node`
+    nodes: NodeRegistry<N>,
     bpm: f32,
+    // a ramp or tempo map moving `bpm` as the song plays
+    tempo: Tempo,
     sr: usize,
     track_amp: f32,
     seed: usize,
+    // the transport position, only moves while playing. It is kept in beats, and the one in
+    // samples is derived from that whenever the tempo or the sample rate changes, so
+    // rounding to whole samples never adds up
+    beats: f64,
     clock: usize,
+    transport: TransportState,
     pub livecoding: bool,
+    quantize: Quantize,
+    // updates waiting for the beat they are due at, oldest first
+    pending: VecDeque<(f64, Box<GraphUpdate<N>>)>,
+    // messages waiting for the beat they are due at, in order
+    events: VecDeque<Event>,
     need_update: bool,
+    taps: Vec<TapWriter>,
+    // frames of the last block `render` did not return yet, interleaved
+    render_rest: Vec<f32>,
 }
 
 impl<const N: usize> Default for Engine<N> {
@@ -55,51 +113,75 @@ impl<const N: usize> Default for Engine<N> {
 
 impl<const N: usize> Engine<N> {
     pub fn new() -> Self {
-        let mut context = AudioContext::<N>::new(AudioContextConfig::default());
+        Self::with_channels(2)
+    }
+
+    /// An engine with `channels` output channels. Chains play on the first two unless they
+    /// end in `out`, like `o: sin 440 >> out 3` for the third.
+    pub fn with_channels(channels: usize) -> Self {
+        let mut context = AudioContext::<N>::new(AudioContextConfig {
+            channels,
+            ..AudioContextConfig::default()
+        });
+        let input = context.add_stereo_node(Pass {});
         let mut index_info = HashMap::new();
-        index_info.insert("~input".to_string(), vec![context.add_stereo_node(Pass {})]);
+        index_info.insert("~input".to_string(), vec![input]);
+        let root = context.add_mono_node(Feedback);
+        context.connect(context.destination, root);
         Self {
             context,
             ast: None,
-            index_info: index_info.clone(),
-            index_info_backup: index_info.clone(),
-            temp_node_index: vec![],
-            samples_dict: HashMap::new(),
+            index_info,
+            input,
+            root,
+            refs: vec![],
+            feedback: vec![],
+            param_values: ParamValues::default(),
+            history: History::default(),
+            scenes: Scenes::default(),
+            fades: vec![],
+            fade_left: 0,
+            crossfade_ms: 0.,
+            samples: SampleBank::new(),
+            nodes: NodeRegistry::default(),
             bpm: 120.,
+            tempo: Tempo::Fixed,
             sr: 44100,
             track_amp: 1.0,
             seed: 42,
+            beats: 0.,
             clock: 0,
+            transport: TransportState::Playing,
             livecoding: true,
+            quantize: Quantize::Immediate,
+            pending: VecDeque::new(),
+            events: VecDeque::new(),
             need_update: false,
+            taps: vec![],
+            render_rest: vec![],
         }
     }
 
+    /// Sends `"chain,position,param,value;"` commands, skipping malformed ones.
+    /// [`Engine::set_param`] reports what it can't find instead.
     pub fn send_msg(&mut self, msg: &str) {
-        let commands: String = msg.chars().filter(|c| !c.is_whitespace()).collect::<_>();
-        for command in commands.split(';').filter(|c| !c.is_empty()) {
-            let mut list = command.split(',');
-            let (Some(chain_name), Some(chain_pos), Some(param_pos), Some(value)) =
-                (list.next(), list.next(), list.next(), list.next())
-            else {
-                continue; // todo: this should be an error
-            };
-
-            let chain_pos = chain_pos.parse::<usize>().unwrap_or_default();
-            let param_pos = param_pos.parse::<u8>().unwrap_or_default();
-            if self.index_info.contains_key(chain_name) {
-                match value.parse::<f32>() {
-                    // todo: check the name and pos
-                    Ok(v) => self.context.graph[self.index_info[chain_name][chain_pos]]
-                        .node
-                        .send_msg(Message::SetToNumber(param_pos, v)),
-                    Err(_) => {
-                        self.context.graph[self.index_info[chain_name][chain_pos]]
-                            .node
-                            .send_msg(Message::SetToSymbol(param_pos, value.to_string()));
-                    }
-                };
+        for (chain_name, chain_pos, message) in parse_msg(msg) {
+            if let Some((index, value)) = ParamValue::from_message(&message) {
+                self.param_values
+                    .insert(&chain_name, chain_pos, index, value);
             }
+            self.send_msg_to(&chain_name, chain_pos, message);
+        }
+    }
+
+    pub(crate) fn send_msg_to(&mut self, chain_name: &str, chain_pos: usize, msg: Message) {
+        self.render_rest.clear();
+        if let Some(index) = self
+            .index_info
+            .get(chain_name)
+            .and_then(|chain| chain.get(chain_pos))
+        {
+            self.context.graph[*index].node.send_msg(msg);
         }
     }
 
@@ -139,400 +221,253 @@ impl<const N: usize> Engine<N> {
         }
     }
 
+    /// Adds a sample for `sp` and `psampler`, with its channels one after another. Replacing
+    /// one swaps it in the nodes already playing it. Samples have one or two channels and
+    /// can't be empty.
+    #[cfg(feature = "use-samples")]
+    pub fn add_sample(
+        &mut self,
+        name: &str,
+        data: impl Into<Arc<[f32]>>,
+        channels: usize,
+        sr: usize,
+    ) -> Result<(), EngineError> {
+        let sample = util::checked_sample(name, data.into(), channels, sr)?;
+        if self.samples.insert(name, sample).is_none() {
+            return Ok(());
+        }
+        let messages = match &self.ast {
+            Some(ast) => util::sample_messages(ast.get(), name, &self.samples),
+            None => vec![],
+        };
+        for (chain_name, chain_pos, msg) in messages {
+            self.send_msg_to(&chain_name, chain_pos, msg);
+        }
+        Ok(())
+    }
+
+    /// Removes a sample from the bank. Nodes playing it keep their copy until they are
+    /// removed, and new nodes can't be built with it.
     #[cfg(feature = "use-samples")]
-    pub fn add_sample(&mut self, name: &str, sample: &'static [f32], channels: usize, sr: usize) {
-        self.samples_dict
-            .insert(name.to_owned(), (sample, channels, sr));
+    pub fn remove_sample(&mut self, name: &str) -> Option<Sample> {
+        self.samples.remove(name)
+    }
+
+    pub fn samples(&self) -> &SampleBank {
+        &self.samples
     }
 
     pub fn reset(&mut self) {
         self.context.reset();
-        self.ast = None;
         self.index_info.clear();
-        self.index_info_backup.clear();
-        self.temp_node_index.clear();
-        self.samples_dict.clear();
+        self.input = self.context.add_stereo_node(Pass {});
+        self.index_info
+            .insert("~input".to_string(), vec![self.input]);
+        self.root = self.context.add_mono_node(Feedback);
+        self.context.connect(self.context.destination, self.root);
+        self.ast = None;
+        self.refs.clear();
+        self.feedback.clear();
+        self.param_values.clear();
+        self.history.clear();
+        self.scenes = Scenes::default();
+        self.fades.clear();
+        self.fade_left = 0;
+        self.samples.clear();
         self.bpm = 120.;
+        self.tempo = Tempo::Fixed;
         self.track_amp = 1.0;
         self.seed = 42;
+        self.beats = 0.;
         self.clock = 0;
+        self.transport = TransportState::Playing;
         self.livecoding = true;
+        self.quantize = Quantize::Immediate;
+        self.pending.clear();
+        self.events.clear();
         self.need_update = false;
+        self.render_rest.clear();
     }
 
-    // This is kind of a mess. But the basic idea is that we want to:
-    // 1. Parse this code to create an AST
-    // 2. Diff that AST against the current AST, create a `GraphDiff` that represents the
-    //       differences. Because that `GraphDiff` will be borrowing from `self`, we need to keep
-    //       it all in this function instead of trying to make it cross fn boundaries to do this in
-    //       multiple different fns because then we'll get some weird borrow errors.
-    // 3. Use that `GraphDiff` to apply the changes that need to be made to `self.context.graph` to
-    //       update it fully to reflect the new syntax
-    // 4. Set self.ast = new_ast;
-    pub fn update_with_code(&mut self, code: &str) -> Result<(), EngineError> {
-        // It would be nice to re-use the allocation in self.ast for this Yoke creation, but I
-        // can't figure out a way to.
-        // We have to make this Yoke here to return it (we can't like make an `Ast` with the same
-        // lifetime as `code` 'cause then it won't have the right lifetime to exist within the
-        // self.ast `Yoke`, and we can't `self.ast.take()` and then re-use the allocation there
-        // 'cause then we'll be unable to do this diffing thing against what it used to be b/c
-        // it'll have been overwritten.
-        let new_ast: YokedAst =
-            Yoke::try_attach_to_cart(code.to_owned().into_boxed_str(), |code| get_ast(code))?;
-
-        self.temp_node_index.clear();
-
-        let mut graph_diff = GraphDiff::default();
-
-        // also remove the whole chain in_old but not_in_new, after ensuring there is no problem with new stuff
-        // println!("\n\nold ast {:?}\n\n new {:?}", self.ast, self.new_ast);
-
-        fn add_nodes<'iter, 'ast: 'iter, const N: usize>(
-            chain_name: &'ast str,
-            iter: impl Iterator<Item = (usize, &'iter Component<'ast>)>,
-            graph_diff: &mut GraphDiff<'ast, N>,
-            samples_dict: &HashMap<String, (&'static [f32], usize, usize)>,
-            sr: usize,
-            bpm: f32,
-            seed: usize,
-        ) -> Result<(), EngineError> {
-            for (i, component) in iter {
-                let (nodedata, reflist) = makenode(component, samples_dict, sr, bpm, seed)?;
-
-                if !reflist.is_empty() {
-                    graph_diff.refpairlist.push((reflist, chain_name, i));
-                }
+    /// Parses `code`, diffs it against the current program (or the last one waiting
+    /// to be applied) and builds every new node, without touching the running graph.
+    /// Errors leave the engine as it was.
+    pub fn prepare(&self, code: &str) -> Result<GraphUpdate<N>, EngineError> {
+        self.planner().plan(code)
+    }
 
-                graph_diff
-                    .node_add_list
-                    .push_back((chain_name, i, nodedata));
-            }
-            Ok(())
+    fn planner(&self) -> Planner<'_, N> {
+        let ast = self.ast.as_ref().map(|ast| ast.get());
+        let last = self.pending.back().map(|(_, update)| update);
+        Planner {
+            ast: match last {
+                Some(update) => update.ast.as_ref().map(|ast| ast.get()),
+                None => ast,
+            },
+            fixed: self
+                .index_info
+                .keys()
+                .map(String::as_str)
+                .filter(|key| !ast.is_some_and(|ast| ast.nodes.contains_key(key)))
+                .collect(),
+            samples: &self.samples,
+            nodes: &self.nodes,
+            sr: self.sr,
+            channels: self.channels(),
+            bpm: self.bpm,
+            seed: self.seed,
+            crossfade_ms: self.crossfade_ms,
+            fade_nodes: match last {
+                Some(update) => update.fade_nodes(),
+                None => self.fades.iter().map(|fade| fade.old.len() + 1).sum(),
+            },
         }
+    }
 
-        if let Some(ref old_ast) = self.ast {
-            for (chain_name, new_chain) in &new_ast.get().nodes {
-                let Some(old_chain) = old_ast.get().nodes.get(chain_name) else {
-                    // if we can't find this chain in the old_ast, we just go through it and add
-                    // every single one to the chain
-                    add_nodes(
-                        chain_name,
-                        new_chain.iter().enumerate(),
-                        &mut graph_diff,
-                        &self.samples_dict,
-                        self.sr,
-                        self.bpm,
-                        self.seed,
-                    )?;
-                    continue;
-                };
-
-                // we gotta go through every node which exists in the old chain and check if it
-                // exists in the new one.
-                for (old_idx, old_comp) in old_chain.iter().enumerate() {
-                    match new_chain
-                        .iter()
-                        .enumerate()
-                        .find(|(_, comp)| old_comp == *comp)
-                    {
-                        // If it exists in the new chain, then we have to update it
-                        Some((idx, new_comp)) => {
-                            // the paras can be the same
-                            // but if the paras are refs, the source chain of refs can change
-                            // e.g. the main chain is o: constsig 42 >> mul ~a
-                            // the ref ~a: constsig 0.5 becomes ~a: constsig 0.5 >> mul 0.5
-                            // need to reconnect them with the ref source
-                            // note that when update, the reflist is cleared,
-                            // so we will need to rebuild all the ref connection anyway
-                            let reflist = new_comp.all_references();
-                            if !reflist.is_empty() {
-                                let owned_reflist =
-                                    reflist.into_iter().map(|s| s.to_owned()).collect();
-                                graph_diff
-                                    .refpairlist
-                                    .push((owned_reflist, chain_name, idx));
-                            }
-                        }
-                        // If it doesn't exist in the new chain,
-                        None => graph_diff.node_remove_list.push((chain_name, old_idx)),
-                    }
+    /// Applies an update made by [`Engine::prepare`] against the current program.
+    /// This can not fail, and leaves the replaced nodes in `update` to be dropped elsewhere.
+    pub fn apply(&mut self, update: &mut GraphUpdate<N>) {
+        // frames rendered ahead by `render` were made by the old graph
+        self.render_rest.clear();
+        // the nodes kept around by the last fades are not needed anymore
+        for fade in &self.fades {
+            for index in fade.old.iter().chain([&fade.xfade]) {
+                if let Some(nodedata) = self.context.graph.remove_node(*index) {
+                    update.garbage.push(nodedata);
                 }
-
-                // then look through the new chain and find everything which doesn't exist in the
-                // old one, and was thus inserted, and track it for insertion
-                add_nodes(
-                    chain_name,
-                    new_chain
-                        .iter()
-                        .enumerate()
-                        .filter(|(_, comp)| !old_chain.iter().any(|old_comp| old_comp == *comp)),
-                    &mut graph_diff,
-                    &self.samples_dict,
-                    self.sr,
-                    self.bpm,
-                    self.seed,
-                )?;
             }
+        }
 
-            // there are some chains show up in old_ast but not in new ast
-            // so we need to figure out what they are and collect them into a Vec
-            graph_diff.idx_to_remove.extend(old_ast.get().nodes.keys()
-                .filter(|key| !new_ast.get().nodes.contains_key(*key))
-                .flat_map(|key| {
-                    // This should be safe to unwrap because index_info should always be consistent
-                    // with self.ast, but we're .expect'ing just to provide a good message in case
-                    self.index_info.remove_entry(*key)
-                        .expect("Index info should be consistent with self.ast, but it turned out to not be. This is a bug.")
-                        .1
-                }));
-
-            // Then remove them all from self
-            while let Some((key, position_in_chain)) = graph_diff.node_remove_list.pop() {
-                if let Some(chain) = self.index_info.get_mut(key) {
-                    // touch the index is fine, as we have a backup
-                    let node_index = chain[position_in_chain];
-
-                    graph_diff.idx_to_remove.push(node_index);
-                    chain.remove(position_in_chain);
-                }
-            }
-        } else {
-            // if the old ast doesn't exist, just add everything to the new graph
-            for (chain_name, new_chain) in &new_ast.get().nodes {
-                add_nodes(
-                    chain_name,
-                    new_chain.iter().enumerate(),
-                    &mut graph_diff,
-                    &self.samples_dict,
-                    self.sr,
-                    self.bpm,
-                    self.seed,
-                )?;
+        for (chain_name, position_in_chain) in &update.removed {
+            let index = self.index_info[chain_name][*position_in_chain];
+            if let Some(nodedata) = self.context.graph.remove_node(index) {
+                update.garbage.push(nodedata);
             }
-        };
-
-        // go through all the nodes that are
-        while let Some((key, position_in_chain, nodedata)) = graph_diff.node_add_list.pop_front() {
-            // TODO: save these id, if there is an error, remove these node
-            let nodeindex = self.context.graph.add_node(nodedata);
+        }
 
-            self.temp_node_index.push(nodeindex);
-            match self.index_info.get_mut(key) {
-                Some(chain) => chain.insert(position_in_chain, nodeindex),
-                None => _ = self.index_info.insert(key.to_string(), vec![nodeindex]),
+        for fade in &mut update.fades {
+            if let Some(old_chain) = self.index_info.get(&fade.chain) {
+                fade.old
+                    .extend(fade.old_positions.iter().map(|j| old_chain[*j]));
+            }
+            if let Some(nodedata) = fade.node.take() {
+                fade.xfade = self.context.graph.add_node(nodedata);
             }
         }
 
-        fn handle_node_update<const N: usize>(
-            graph_diff: &mut GraphDiff<'_, N>,
-            index_info: &HashMap<String, Vec<NodeIndex>>,
-            graph: &mut GlicolGraph<N>,
-            samples_dict: &mut HashMap<String, (&'static [f32], usize, usize)>,
-        ) -> Result<(), EngineError> {
-            while let Some((key, position_in_chain, paras)) = graph_diff.node_update_list.pop() {
-                // println!("handle update {:?} {:?}", key, position_in_chain);
-                if let Some(chain) = index_info.get(key) {
-                    // TODO: reset order here, if ref is wrong, cannot be reverted
-                    // self.context.graph[
-                    //     chain[position_in_chain]].node.send_msg(Message::ResetOrder);
-                    for (i, para) in paras.iter().enumerate() {
-                        match para {
-                            GlicolPara::Number(v) => graph[chain[position_in_chain]]
-                                .node
-                                .send_msg(Message::SetToNumber(i as u8, *v)),
-                            GlicolPara::Reference(s) => {
-                                graph_diff.refpairlist.push((
-                                    vec![s.to_string()],
-                                    key,
-                                    position_in_chain,
-                                ));
-                            }
-                            GlicolPara::SampleSymbol(s) => {
-                                let Some(sample) = samples_dict.get(*s) else {
-                                    return Err(EngineError::NonExistSample(s.to_string()));
-                                };
-
-                                graph[chain[position_in_chain]]
-                                    .node
-                                    .send_msg(Message::SetToSamples(i as u8, *sample))
-                            }
-                            GlicolPara::Points(_p) => graph[chain[position_in_chain]]
-                                .node
-                                .send_msg(Message::SetParam(i as u8, (*para).to_inner_owned())),
-                            GlicolPara::Bool(b) => graph[chain[position_in_chain]]
-                                .node
-                                .send_msg(Message::SetToBool(i as u8, *b)),
-                            GlicolPara::Symbol(s) => graph[chain[position_in_chain]]
+        for (chain_name, slots) in &update.layout {
+            let Some(chain) = update.index_info.get_mut(chain_name) else {
+                continue;
+            };
+            for slot in slots {
+                let index = match *slot {
+                    Slot::Keep(i) => self.index_info[chain_name][i],
+                    Slot::New(i) => {
+                        let index = self.context.graph.add_node(
+                            update.nodes[i]
+                                .take()
+                                .expect("A prepared node can only be added once"),
+                        );
+                        // the tempo moved after the update was prepared
+                        if update.bpm != self.bpm {
+                            self.context.graph[index]
                                 .node
-                                .send_msg(Message::SetToSymbol(i as u8, s.to_string())),
-                            GlicolPara::Sequence(events) => {
-                                // println!("found seq in update, process it {:?}", events);
-                                // todo: an issue is that when you revert it, these messages cannot be undone
-                                graph[chain[position_in_chain]]
-                                    .node
-                                    .send_msg(Message::SetToSeq(i as u8, events.to_inner_owned()));
-                                let mut reflist: Vec<String> = vec![];
-                                let mut count = 0;
-                                let mut order = hashbrown::HashMap::new();
-                                for event in events {
-                                    if let UsizeOrRef::Ref(s) = &event.1 {
-                                        // reflist: ["~a", "~b", "~a"]
-                                        if !reflist.iter().any(|r| r == s) {
-                                            reflist.push(s.to_string());
-                                            order.insert(s.to_string(), count);
-                                            count += 1;
-                                        }
-                                    }
-                                }
-                                graph_diff
-                                    .refpairlist
-                                    .push((reflist, key, position_in_chain));
-                                graph[chain[position_in_chain]]
-                                    .node
-                                    .send_msg(Message::SetRefOrder(order));
-                            }
-                            GlicolPara::NumberList(l) => graph[chain[position_in_chain]]
+                                .send_msg(Message::SetBPM(self.bpm));
+                        }
+                        // new nodes join the song where it is
+                        self.context.graph[index]
+                            .node
+                            .send_msg(Message::SetPosition(self.clock));
+                        self.context.smooth_node(index);
+                        // the sample rate changed after the update was prepared
+                        if update.sr != self.sr {
+                            self.context.graph[index]
                                 .node
-                                .send_msg(Message::SetToNumberList(i as u8, l.clone())),
-                            GlicolPara::Pattern(value_time_list, span) => {
-                                // todo, differ a symbol pattern and number pattern?
-                                let mut samples_dict_selected = HashMap::new();
-                                let mut symbol_pattern = vec![];
-                                let mut number_pattern = vec![];
-
-                                for value_time in value_time_list.iter() {
-                                    let time = value_time.1;
-                                    match &value_time.0 {
-                                        GlicolPara::Number(num) => {
-                                            number_pattern.push((*num, time))
-                                        }
-                                        GlicolPara::Symbol(s) => {
-                                            let Some(sample) = samples_dict.get(*s) else {
-                                                return Err(EngineError::NonExistSample(
-                                                    s.to_string(),
-                                                ));
-                                            };
-
-                                            samples_dict_selected.insert(s.to_string(), *sample);
-                                            symbol_pattern.push((s.to_string(), time));
-                                        }
-                                        _ => unimplemented!(),
-                                    };
-                                    // pattern.push((value, time));
-                                }
-
-                                if !symbol_pattern.is_empty() {
-                                    graph[chain[position_in_chain]].node.send_msg(
-                                        Message::SetSamplePattern(
-                                            symbol_pattern,
-                                            *span,
-                                            samples_dict_selected,
-                                        ),
-                                    )
-                                } else {
-                                    graph[chain[position_in_chain]]
-                                        .node
-                                        .send_msg(Message::SetPattern(number_pattern, *span))
-                                }
-                            }
-
-                            _ => {}
+                                .send_msg(Message::SetSampleRate(self.sr));
                         }
+                        index
                     }
-
-                    // self.context.send_msg(index: NodeIndex, msg: Message)
-                }
+                };
+                chain.push(index);
             }
-            Ok(())
         }
 
-        if let Err(e) = handle_node_update(
-            &mut graph_diff,
-            &self.index_info,
-            &mut self.context.graph,
-            &mut self.samples_dict,
-        ) {
-            return Err(self.clean_up(e));
-        };
-
-        // verify that all refs that are mentioned in the refpairlist do, in fact, exist in the
-        // new_ast or current index_info and will thus work when we try to connect the graph
-        fn handle_ref_check(
-            refpairlist: &[(Vec<String>, &str, usize)],
-            index_info: &HashMap<String, Vec<NodeIndex>>,
-            new_ast: &YokedAst,
-        ) -> Result<(), EngineError> {
-            // ref pair is like (~mod -> a node [e.g key: out, pos_in_chain: 3])
-            // ref check should use the new ast hashmap
-            // because old ast hashmap has something that may need to be deleted
-            // println!("ref check {:?}", self.refpairlist);
-
-            for (names, _, _) in refpairlist {
-                for refname in names {
-                    // println!("ref check {} {}", self.new_ast.contains_key(refname), refname);
-                    if refname.contains("..") {
-                        // println!("look for {}", &refname.replace("..", ""));
-                        if !index_info
-                            .keys()
-                            .any(|key| key.starts_with(&refname.replace("..", "")))
-                        {
-                            return Err(EngineError::NonExistReference(refname.to_owned()));
-                        }
-                    } else if !new_ast.get().nodes.contains_key(&**refname)
-                        && !index_info.contains_key(refname)
-                    {
-                        return Err(EngineError::NonExistReference(refname.to_owned()));
-                    }
-                }
+        for key in &update.fixed {
+            if let Some((key, chain)) = self.index_info.remove_entry(key) {
+                update.index_info.insert(key, chain);
             }
-            Ok(())
         }
 
-        match handle_ref_check(&graph_diff.refpairlist, &self.index_info, &new_ast) {
-            Ok(_) => {
-                // println!(" ref check &self.node_index_to_remove {:?}", &self.node_index_to_remove);
-                for id in &graph_diff.idx_to_remove {
-                    self.context.graph.remove_node(*id);
-                }
-            }
-            Err(e) => return Err(self.clean_up(e)),
+        std::mem::swap(&mut self.index_info, &mut update.index_info);
+        std::mem::swap(&mut self.ast, &mut update.ast);
+        std::mem::swap(&mut self.refs, &mut update.refs);
+        std::mem::swap(&mut self.feedback, &mut update.feedback);
+        std::mem::swap(&mut self.fades, &mut update.fades);
+        self.param_values.follow(update);
+        for (chain_name, position_in_chain, msg) in &mut update.messages {
+            // the chain names stay in the update, so nothing is freed here
+            let msg = std::mem::replace(msg, Message::ResetOrder);
+            self.send_msg_to(chain_name, *position_in_chain, msg);
+        }
+        self.fade_left = if self.fades.is_empty() {
+            0
+        } else {
+            update.fade_len
         };
+        self.connect_graph();
+    }
 
+    // rebuild every edge from the chains, the refs and the running fades
+    fn connect_graph(&mut self) {
+        let fades: &[Fade<N>] = if self.fade_left > 0 { &self.fades } else { &[] };
         self.context.graph.clear_edges();
-        // println!("self.index_info in handle_connection{:?}", self.index_info);
-        // println!("self.refpairlist in handle_connection {:?}", self.refpairlist);
-
-        // now we have to go through and actually make the graph with all the connections we have
-        let mut already_reset = std::collections::HashSet::new();
-        for (reflist, name, new_idx) in &graph_diff.refpairlist {
-            let Some(chain) = self.index_info.get(*name) else {
-                return Err(EngineError::NonExistReference(name.to_string()));
-            };
 
-            let index = chain[*new_idx];
+        let fade_nodes = fades
+            .iter()
+            .flat_map(|fade| fade.old.iter().chain([&fade.xfade]));
+        for index in self.index_info.values().flatten().chain(fade_nodes) {
+            self.context.graph[*index]
+                .node
+                .send_msg(Message::ResetOrder);
+        }
 
-            if !already_reset.contains(&index) {
-                self.context.graph[index].node.send_msg(Message::ResetOrder);
-                already_reset.insert(index);
-            }
+        // what other nodes read from a chain: its last node, or the fade at its end
+        let output = |chain_name: &str| {
+            fades
+                .iter()
+                .find(|fade| fade.chain == chain_name && fade.before.is_none())
+                .map(|fade| fade.xfade)
+                .or_else(|| self.index_info.get(chain_name)?.last().copied())
+        };
 
+        // ref connections come first, then the main input is inserted at position 0
+        for (chain_name, position_in_chain, reflist) in &self.refs {
+            let index = self.index_info[chain_name][*position_in_chain];
             for refname in reflist {
-                if refname.contains("..") {
-                    // println!("look for {}", &refname.replace("..", ""));
-                    for (key, value) in self.index_info.iter() {
-                        if key.starts_with(&refname.replace("..", "")) {
-                            self.context.connect(*value.last().unwrap(), index);
-                        }
+                if let Some(source) = output(refname) {
+                    self.context.connect(source, index);
+                }
+            }
+        }
+        for fade in fades {
+            for (k, reflist) in &fade.old_refs {
+                for refname in reflist {
+                    if let Some(source) = output(refname) {
+                        self.context.connect(source, fade.old[*k]);
                     }
-                } else {
-                    self.context
-                        .connect(*self.index_info[refname].last().unwrap(), index);
                 }
             }
         }
 
         for (key, chain) in &self.index_info {
-            for window in chain.windows(2) {
+            for (i, window) in chain.windows(2).enumerate() {
+                // a fade in front of a node takes the place of its main input
+                if fades
+                    .iter()
+                    .any(|fade| fade.chain == *key && fade.before == Some(i + 1))
+                {
+                    continue;
+                }
                 // this is guaranteed to succeed as long as the argument to windows is 2
                 // TODO when array_windows is stabilized, change over to that
                 if let [start, end] = window {
@@ -540,69 +475,217 @@ impl<const N: usize> Engine<N> {
                 }
             }
             if !key.contains('~') {
-                if let Some(end) = chain.last() {
+                if let Some(end) = output(key) {
+                    self.context
+                        .connect_with_order(end, self.context.destination, 0);
+                }
+            }
+        }
+
+        for fade in fades {
+            let chain = self.index_info.get(&fade.chain);
+            let node_at = |i: Option<usize>| chain.and_then(|chain| chain.get(i?)).copied();
+
+            // the old nodes keep running as a chain of their own, next to the new ones
+            let mut from = node_at(fade.after);
+            for index in &fade.old {
+                if let Some(from) = from {
+                    self.context.connect_with_order(from, *index, 0);
+                }
+                from = Some(*index);
+            }
+            if let Some(from) = from {
+                self.context.connect_with_order(from, fade.xfade, 0);
+            }
+            if let Some(to) = node_at(fade.new_end()) {
+                self.context.connect_with_order(to, fade.xfade, 1);
+            }
+
+            match node_at(fade.before) {
+                Some(next) => _ = self.context.connect_with_order(fade.xfade, next, 0),
+                // a chain that was removed fades out straight into the output
+                None if chain.is_none() && !fade.chain.contains('~') => {
                     self.context
-                        .connect_with_order(*end, self.context.destination, 0);
+                        .connect_with_order(fade.xfade, self.context.destination, 0);
                 }
+                None => {}
             }
         }
 
-        // We can't reuse the allocation here as far as I can tell; see the comment at the top of
-        // Self::parse
-        self.ast = Some(new_ast);
-        self.index_info_backup.clone_from(&self.index_info);
+        self.context.connect(self.context.destination, self.root);
+        let old_feedback = fades
+            .iter()
+            .flat_map(|fade| fade.old_feedback.iter().map(|(_, sources)| sources));
+        let sources = self.feedback.iter().map(|(_, _, sources)| sources);
+        for source in sources.chain(old_feedback).flatten() {
+            if let Some(&from) = self.index_info.get(source).and_then(|chain| chain.last()) {
+                self.context.connect(from, self.root);
+            }
+        }
+    }
+
+    /// Parses, diffs and applies `code` in one go. On error nothing is changed.
+    /// In livecoding mode the update waits for the boundary set with
+    /// [`Engine::set_quantize`].
+    pub fn update_with_code(&mut self, code: &str) -> Result<(), EngineError> {
+        let update = self.prepare(code)?;
+        self.schedule_update(Box::new(update));
+        self.history.push(code);
+        self.scenes.set_current(None);
         Ok(())
     }
 
-    pub fn clean_up(&mut self, e: EngineError) -> EngineError {
-        // remove the added node
-        // use the old index
-        for id in &self.temp_node_index {
-            // println!("graph.remove_node in clean_up {:?}", *id);
-            self.context.graph.remove_node(*id);
-        }
-        self.index_info.clone_from(&self.index_info_backup);
-        e
+    pub fn next_block(&mut self, buf: &[&[f32]]) -> &[Buffer<N>] {
+        self.render_rest.clear();
+        self.render_block(buf, drop)
     }
 
-    pub fn next_block(&mut self, buf: Vec<&[f32]>) -> &[Buffer<N>] {
-        //  -> &Vec<Buffer<N>>
+    // hands the chain names of the scheduled messages it sends to `done`
+    pub(crate) fn render_block(
+        &mut self,
+        buf: &[&[f32]],
+        done: impl FnMut(String),
+    ) -> &[Buffer<N>] {
+        self.apply_due(drop);
+        if self.transport != TransportState::Playing {
+            for buf in self.context.graph[self.context.destination]
+                .buffers
+                .iter_mut()
+            {
+                buf.silence();
+            }
+            return &self.context.graph[self.context.destination].buffers;
+        }
         if !buf.is_empty() {
-            self.context.graph[self.index_info[&"~input".to_string()][0]].buffers[0]
-                .copy_from_slice(buf[0]);
+            self.context.graph[self.input].buffers[0].copy_from_slice(buf[0]);
         }
 
         if buf.len() > 1 {
-            self.context.graph[self.index_info[&"~input".to_string()][0]].buffers[1]
-                .copy_from_slice(buf[1]);
+            self.context.graph[self.input].buffers[1].copy_from_slice(buf[1]);
         }
 
+        self.follow_tempo();
+        self.send_due_events(done);
+        self.feed_back();
         self.context
             .processor
-            .process(&mut self.context.graph, self.context.destination);
-        // println!("result {:?}", &self.context.graph[self.context.destination].buffers);
+            .process(&mut self.context.graph, self.root);
+        self.write_taps();
         self.clock += N;
+        self.beats += self.beats_at(N);
+        if self.fade_left > 0 {
+            self.fade_left = self.fade_left.saturating_sub(N);
+            if self.fade_left == 0 {
+                self.connect_graph();
+            }
+        }
         &self.context.graph[self.context.destination].buffers
     }
 
+    // the `fb` nodes play what the chains they read played in the last block
+    fn feed_back(&mut self) {
+        let fades: &[Fade<N>] = if self.fade_left > 0 { &self.fades } else { &[] };
+        let old = fades.iter().flat_map(|fade| {
+            fade.old_feedback
+                .iter()
+                .map(|(k, sources)| (fade.old[*k], sources))
+        });
+        let index_info = &self.index_info;
+        let feedback = self
+            .feedback
+            .iter()
+            .filter_map(|(chain_name, position, sources)| {
+                Some((*index_info.get(chain_name)?.get(*position)?, sources))
+            });
+        for (index, sources) in feedback.chain(old) {
+            for buffer in &mut self.context.graph[index].buffers {
+                buffer.silence();
+            }
+            for source in sources {
+                let Some(&from) = index_info.get(source).and_then(|chain| chain.last()) else {
+                    continue;
+                };
+                if from == index {
+                    continue;
+                }
+                let (from, to) = self.context.graph.index_twice_mut(from, index);
+                for (channel, buffer) in to.buffers.iter_mut().enumerate() {
+                    // a mono chain goes to both channels
+                    let Some(input) = from.buffers.get(channel).or(from.buffers.first()) else {
+                        continue;
+                    };
+                    for (out, sample) in buffer.iter_mut().zip(input.iter()) {
+                        *out += sample;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Changes the tempo right away, stopping any ramp or tempo map. Nodes on the song grid
+    /// and updates waiting for a boundary keep their place in the bar.
     pub fn set_bpm(&mut self, bpm: f32) {
-        self.bpm = bpm;
-        self.context.send_msg_to_all(Message::SetBPM(bpm));
+        self.tempo = Tempo::Fixed;
+        self.change_bpm(bpm);
     }
-    
+
     pub fn get_bpm(&self) -> f32 {
         self.bpm
     }
 
+    /// Changes the sample rate of the running nodes and of the ones built later, keeping
+    /// the transport at the same point in time. Delay lines are reallocated.
     pub fn set_sr(&mut self, sr: usize) {
-        self.sr = sr
+        self.render_rest.clear();
+        self.sr = sr;
+        self.clock = self.samples_at(self.beats);
+        self.context.set_sr(sr);
+        self.context
+            .send_msg_to_all(Message::SetPosition(self.clock));
     }
 
+    /// When code is updated, removed and changed nodes keep playing and fade out over `ms`
+    /// while the new ones fade in. 0 (the default) switches instantly, and changed
+    /// parameters are set on the running nodes.
+    pub fn set_update_crossfade_ms(&mut self, ms: f32) {
+        self.crossfade_ms = ms.max(0.);
+    }
+
+    /// Sets the seed every random node derives its own from, by its chain and position.
+    /// The same code with the same seed always renders the same samples. Running nodes
+    /// restart from their new seeds; updates already prepared keep the old one.
     pub fn set_seed(&mut self, seed: usize) {
-        self.seed = seed
+        self.seed = seed;
+        for (chain, indices) in &self.index_info {
+            for (position, index) in indices.iter().enumerate() {
+                self.context.graph[*index]
+                    .node
+                    .send_msg(Message::SetSeed(node_seed(seed, chain, position)));
+            }
+        }
     }
+
+    pub fn seed(&self) -> usize {
+        self.seed
+    }
+
+    /// The number of output channels, the length of every block `next_block` returns.
+    pub fn channels(&self) -> usize {
+        self.context.graph[self.context.destination].buffers.len()
+    }
+
+    /// The gain of the master stage, see [`Engine::set_master`].
     pub fn set_track_amp(&mut self, amp: f32) {
-        self.track_amp = amp
+        self.track_amp = amp;
+        self.context
+            .send_msg(self.context.destination, Message::SetToNumber(0, amp));
+    }
+
+    /// Sets up the master stage every chain is summed into. It is off by default;
+    /// [`MasterConfig::safe`] turns on DC blocking, a limiter and a mute for NaN or
+    /// infinite samples. The limiter delays the output by its lookahead.
+    pub fn set_master(&mut self, master: MasterConfig) {
+        self.context.set_master(master);
     }
 
     #[cfg(test)]
@@ -0,0 +1,238 @@
+use glicol_parser::nodes::{Ast, Component};
+use glicol_synth::{
+    compound::{Bd, Hh, SawSynth, Sn, SquSynth, TriSynth},
+    delay::{DelayMs, DelayN},
+    dynamic::Eval,
+    effect::{Pan, Plate},
+    envelope::{Adsr, EnvPerc},
+    filter::{AllPassFilterGain, OnePole, ResonantHighPassFilter, ResonantLowPassFilter},
+    operator::{Add, Mul},
+    oscillator::{SawOsc, SinOsc, SquOsc, TriOsc},
+    sequencer::{Choose, Speed},
+    signal::{ConstSig, Impulse, Noise},
+    synth::{MsgSynth, PatternSynth},
+    Message, ParamInfo, ParamKind, Smoothing,
+};
+
+#[cfg(feature = "use-meta")]
+use glicol_synth::dynamic::Meta;
+
+use crate::{registry::NodeRegistry, Engine, EngineError};
+
+/// Which node of a chain [`Engine::set_param`] talks to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeSelector<'a> {
+    /// The node at this position, counted from 0.
+    Position(usize),
+    /// The first node of this type, like `"lpf"`.
+    Type(&'a str),
+}
+
+/// A parameter of a node, by its position in the code or by name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamId<'a> {
+    Index(u8),
+    Name(&'a str),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParamValue {
+    Number(f32),
+    Bool(bool),
+    Symbol(String),
+    NumberList(Vec<f32>),
+}
+
+impl From<f32> for ParamValue {
+    fn from(value: f32) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl ParamValue {
+    pub fn kind(&self) -> ParamKind {
+        match self {
+            Self::Number(_) => ParamKind::Number,
+            Self::Bool(_) => ParamKind::Bool,
+            Self::Symbol(_) => ParamKind::Symbol,
+            Self::NumberList(_) => ParamKind::NumberList,
+        }
+    }
+
+    /// The parameter index and value a `Message::SetTo*` sets.
+    pub(crate) fn from_message(msg: &Message) -> Option<(u8, Self)> {
+        Some(match msg {
+            Message::SetToNumber(index, v) => (*index, Self::Number(*v)),
+            Message::SetToBool(index, v) => (*index, Self::Bool(*v)),
+            Message::SetToSymbol(index, v) => (*index, Self::Symbol(v.clone())),
+            Message::SetToNumberList(index, v) => (*index, Self::NumberList(v.clone())),
+            _ => return None,
+        })
+    }
+
+    pub(crate) fn into_message(self, index: u8) -> Message {
+        match self {
+            Self::Number(v) => Message::SetToNumber(index, v),
+            Self::Bool(v) => Message::SetToBool(index, v),
+            Self::Symbol(v) => Message::SetToSymbol(index, v),
+            Self::NumberList(v) => Message::SetToNumberList(index, v),
+        }
+    }
+}
+
+/// The parameters a node can change while running, as described by the node itself.
+pub fn component_params<const N: usize>(component: &Component<'_>) -> &'static [ParamInfo] {
+    match component {
+        Component::Sin(_) => SinOsc::PARAMS,
+        Component::Saw(_) => SawOsc::PARAMS,
+        Component::Squ(_) => SquOsc::PARAMS,
+        Component::Tri(_) => TriOsc::PARAMS,
+        Component::Imp(_) => Impulse::PARAMS,
+        Component::Mul(_) => Mul::PARAMS,
+        Component::Add(_) => Add::PARAMS,
+        Component::ConstSig(_) => ConstSig::PARAMS,
+        Component::Pan(_) => Pan::PARAMS,
+        Component::Onepole(_) => OnePole::PARAMS,
+        Component::Delayn(_) => DelayN::PARAMS,
+        Component::Delayms(_) => DelayMs::PARAMS,
+        Component::Speed(_) => Speed::PARAMS,
+        Component::Plate(_) => Plate::<N>::PARAMS,
+        Component::Noise(_) => Noise::PARAMS,
+        Component::Choose(_) => Choose::PARAMS,
+        Component::Lpf(_) => ResonantLowPassFilter::PARAMS,
+        Component::Rhpf(_) => ResonantHighPassFilter::PARAMS,
+        Component::ApfmsGain(_) => AllPassFilterGain::PARAMS,
+        Component::Bd(_) => Bd::<N>::PARAMS,
+        Component::Sn(_) => Sn::<N>::PARAMS,
+        Component::Hh(_) => Hh::<N>::PARAMS,
+        Component::EnvPerc(_) => EnvPerc::PARAMS,
+        Component::SawSynth(_) => SawSynth::<N>::PARAMS,
+        Component::SquSynth(_) => SquSynth::<N>::PARAMS,
+        Component::TriSynth(_) => TriSynth::<N>::PARAMS,
+        Component::Adsr(_) => Adsr::PARAMS,
+        Component::MsgSynth(_) => MsgSynth::PARAMS,
+        Component::PatternSynth(_) => PatternSynth::PARAMS,
+        Component::Eval(_) => Eval::<N>::PARAMS,
+        #[cfg(feature = "use-meta")]
+        Component::Meta(_) => Meta::<N>::PARAMS,
+        _ => &[],
+    }
+}
+
+/// Finds the node in the running program, with its position in the chain.
+pub(crate) fn find_node<'a>(
+    ast: Option<&'a Ast<'a>>,
+    chain_name: &str,
+    node: NodeSelector<'_>,
+) -> Result<(usize, &'a Component<'a>), EngineError> {
+    let chain = ast
+        .and_then(|ast| ast.nodes.get(chain_name))
+        .ok_or_else(|| EngineError::NonExistReference(chain_name.to_string()))?;
+    match node {
+        NodeSelector::Position(i) => chain.get(i).map(|component| (i, component)),
+        NodeSelector::Type(name) => chain
+            .iter()
+            .enumerate()
+            .find(|(_, component)| component.name() == name),
+    }
+    .ok_or_else(|| EngineError::NonExistNode(format!("{node:?} in {chain_name}")))
+}
+
+/// Finds the node and parameter in the running program, with the position of the node.
+fn find_param<'a, const N: usize>(
+    ast: Option<&'a Ast<'a>>,
+    nodes: &NodeRegistry<N>,
+    chain_name: &str,
+    node: NodeSelector<'_>,
+    param: ParamId<'_>,
+) -> Result<(usize, &'a str, &'static ParamInfo), EngineError> {
+    let (position, component) = find_node(ast, chain_name, node)?;
+    let params = nodes.params(component);
+    let info = match param {
+        ParamId::Index(index) => params.iter().find(|info| info.index == index),
+        ParamId::Name(name) => params.iter().find(|info| info.name == name),
+    }
+    .ok_or_else(|| EngineError::NonExistParam(format!("{param:?} of {}", component.name())))?;
+    Ok((position, component.name(), info))
+}
+
+/// Finds the node and parameter in the running program and makes the message for them.
+pub(crate) fn param_message<const N: usize>(
+    ast: Option<&Ast<'_>>,
+    nodes: &NodeRegistry<N>,
+    chain_name: &str,
+    node: NodeSelector<'_>,
+    param: ParamId<'_>,
+    value: ParamValue,
+) -> Result<(usize, Message), EngineError> {
+    let (position, name, info) = find_param(ast, nodes, chain_name, node, param)?;
+    if value.kind() != info.kind {
+        return Err(EngineError::InvalidParamValue(format!(
+            "{} of {name} takes a {:?}, not {value:?}",
+            info.name, info.kind
+        )));
+    }
+    Ok((position, value.into_message(info.index)))
+}
+
+/// Like [`param_message`], for changing how the parameter is smoothed.
+pub(crate) fn smoothing_message<const N: usize>(
+    ast: Option<&Ast<'_>>,
+    nodes: &NodeRegistry<N>,
+    chain_name: &str,
+    node: NodeSelector<'_>,
+    param: ParamId<'_>,
+    smoothing: Smoothing,
+) -> Result<(usize, Message), EngineError> {
+    let (position, name, info) = find_param(ast, nodes, chain_name, node, param)?;
+    if !info.smoothed {
+        return Err(EngineError::InvalidParamValue(format!(
+            "{} of {name} can not be smoothed",
+            info.name
+        )));
+    }
+    Ok((position, Message::SetSmoothing(info.index, smoothing)))
+}
+
+impl<const N: usize> Engine<N> {
+    /// Sets a parameter of a running node, e.g.
+    /// `set_param("o", NodeSelector::Type("lpf"), ParamId::Name("cutoff"), 800.0.into())`.
+    pub fn set_param(
+        &mut self,
+        chain_name: &str,
+        node: NodeSelector<'_>,
+        param: ParamId<'_>,
+        value: ParamValue,
+    ) -> Result<(), EngineError> {
+        let ast = self.ast.as_ref().map(|ast| ast.get());
+        let (position, msg) =
+            param_message(ast, &self.nodes, chain_name, node, param, value.clone())?;
+        if let Some((index, _)) = ParamValue::from_message(&msg) {
+            self.param_values.insert(chain_name, position, index, value);
+        }
+        self.send_msg_to(chain_name, position, msg);
+        Ok(())
+    }
+
+    /// Sets how a parameter of a running node moves to new values, overriding
+    /// [`Engine::set_smoothing`] for it. Only parameters marked `smoothed` can be smoothed.
+    pub fn set_param_smoothing(
+        &mut self,
+        chain_name: &str,
+        node: NodeSelector<'_>,
+        param: ParamId<'_>,
+        smoothing: Smoothing,
+    ) -> Result<(), EngineError> {
+        let ast = self.ast.as_ref().map(|ast| ast.get());
+        let (position, msg) =
+            smoothing_message(ast, &self.nodes, chain_name, node, param, smoothing)?;
+        self.send_msg_to(chain_name, position, msg);
+        Ok(())
+    }
+
+    /// Smooths every parameter that supports it, in running nodes and in nodes added later.
+    pub fn set_smoothing(&mut self, smoothing: Smoothing) {
+        self.context.set_smoothing(smoothing);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{Engine, GraphUpdate, TransportState};
+
+/// When code updates take effect while [`Engine::livecoding`] is on.
+/// A bar is 4 beats long, like the bar of `seq`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Quantize {
+    /// Apply updates right away.
+    #[default]
+    Immediate,
+    /// Wait for the next beat.
+    Beat,
+    /// Wait for the next bar.
+    Bar,
+    /// Wait for the next multiple of this many bars.
+    Bars(u32),
+}
+
+impl Quantize {
+    /// The first boundary at or after `beat`, in beats, or `None` to apply immediately.
+    /// Beats don't depend on the tempo, so this holds through ramps and tempo maps.
+    pub fn next_boundary(self, beat: f64) -> Option<f64> {
+        let period = self.beats()?;
+        // a beat a rounding error past a boundary is on it
+        Some(((beat - 1e-9) / period).ceil().max(0.) * period)
+    }
+
+    fn beats(self) -> Option<f64> {
+        Some(match self {
+            Quantize::Immediate => return None,
+            Quantize::Beat => 1.,
+            Quantize::Bar => 4.,
+            Quantize::Bars(bars) => 4. * bars.max(1) as f64,
+        })
+    }
+}
+
+impl<const N: usize> Engine<N> {
+    /// Picks when code updates are applied in livecoding mode.
+    pub fn set_quantize(&mut self, quantize: Quantize) {
+        self.quantize = quantize;
+    }
+
+    pub fn get_quantize(&self) -> Quantize {
+        self.quantize
+    }
+
+    /// Applies `update` now and returns it with what it replaced, or queues it until
+    /// the next boundary. An update never overtakes one that is still waiting.
+    pub(crate) fn schedule_update(
+        &mut self,
+        update: Box<GraphUpdate<N>>,
+    ) -> Option<Box<GraphUpdate<N>>> {
+        let quantize = match self.livecoding {
+            true => self.quantize,
+            false => Quantize::Immediate,
+        };
+        self.schedule_update_with(update, quantize)
+    }
+
+    /// Like [`Engine::schedule_update`], waiting for the boundary of `quantize` instead.
+    pub(crate) fn schedule_update_with(
+        &mut self,
+        mut update: Box<GraphUpdate<N>>,
+        quantize: Quantize,
+    ) -> Option<Box<GraphUpdate<N>>> {
+        let due = quantize.next_boundary(self.beats);
+        match (due, self.pending.back()) {
+            (None, None) => {
+                self.apply(&mut update);
+                Some(update)
+            }
+            (due, last) => {
+                let due = due
+                    .unwrap_or(self.beats)
+                    .max(last.map_or(0., |(last, _)| *last));
+                self.pending.push_back((due, update));
+                None
+            }
+        }
+    }
+
+    /// Applies the waiting updates whose boundary falls in the coming block (or all of
+    /// them when the transport is not playing), handing each one to `done` afterwards.
+    pub(crate) fn apply_due(&mut self, mut done: impl FnMut(Box<GraphUpdate<N>>)) {
+        let playing = self.transport == TransportState::Playing;
+        while let Some((due, _)) = self.pending.front() {
+            if playing && self.samples_at(*due) >= self.clock + N {
+                break;
+            }
+            if let Some((_, mut update)) = self.pending.pop_front() {
+                self.apply(&mut update);
+                done(update);
+            }
+        }
+    }
+
+    /// Whether code updates are waiting for a boundary.
+    pub fn has_pending_update(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
@@ -0,0 +1,621 @@
+//! Running an [`Engine`] from an audio callback without locks.
+//!
+//! [`Engine::split`] turns an engine into an [`EngineHandle`] for the control thread and an
+//! [`EngineProcessor`] for the audio thread. The handle parses code and builds nodes, then
+//! sends the prepared changes through a wait-free queue; the processor applies them between
+//! blocks and sends the updates, samples, taps and names it is done with back, so it never
+//! locks or parses and frees none of those.
+//!
+//! Some messages still allocate or free inside the node they reach: a new sequence, sample
+//! pattern or reference order replaces the old one in place, and a new seed rebuilds the
+//! random generator of `noise`.
+
+use glicol_synth::{Buffer, MasterConfig, Message, ParamInfo, Sample, SampleBank, Smoothing};
+use rtrb::{Consumer, Producer, RingBuffer};
+#[cfg(feature = "use-samples")]
+use std::sync::Arc;
+
+use crate::{
+    describe::{describe, ParamValues},
+    diff::Planner,
+    param::{find_node, param_message, smoothing_message},
+    registry::NodeRegistry,
+    scene::Scenes,
+    tap::TapWriter,
+    tempo::Tempo,
+    util::parse_msg,
+    Engine, EngineError, EventTime, GlicolNodeData, GraphDescription, GraphUpdate, History,
+    NodeArg, NodeSelector, ParamId, ParamValue, Quantize, TempoMap, TransportState, YokedAst,
+};
+
+enum Command<const N: usize> {
+    Update(Box<GraphUpdate<N>>),
+    // an update waiting for its own boundary, even when not livecoding
+    QuantizedUpdate(Box<GraphUpdate<N>>, Quantize),
+    SetBpm(f32),
+    // (bpm, bars)
+    RampBpm(f32, f32),
+    SetTempoMap(TempoMap),
+    SetQuantize(Quantize),
+    SetTransport(TransportState),
+    Seek(f32),
+    SetSmoothing(Smoothing),
+    SetSampleRate(usize),
+    SetMaster(MasterConfig),
+    SetTrackAmp(f32),
+    SetSeed(usize),
+    Message(String, usize, Message),
+    // (when, chain, position in chain, message)
+    Schedule(EventTime, String, usize, Message),
+    // the taps to write from now on, replacing the current ones
+    SetTaps(Vec<TapWriter>),
+    // a replaced sample, sent back once the nodes playing it have let go
+    #[cfg(feature = "use-samples")]
+    Retire(Sample),
+}
+
+// things the audio thread is done with, dropped on the control thread
+#[allow(dead_code)]
+enum Garbage<const N: usize> {
+    Update(Box<GraphUpdate<N>>),
+    Name(String),
+    Sample(Sample),
+    Taps(Vec<TapWriter>),
+    Tempo(Tempo),
+}
+
+impl<const N: usize> Engine<N> {
+    /// Splits the engine into a control handle and an audio processor,
+    /// connected by queues that can hold `capacity` pending changes.
+    pub fn split(mut self, capacity: usize) -> (EngineHandle<N>, EngineProcessor<N>) {
+        let (command_tx, command_rx) = RingBuffer::new(capacity);
+        // room for what the commands replace and for the names of the scheduled messages
+        let (garbage_tx, garbage_rx) = RingBuffer::new(capacity * 2);
+        // quantized updates and scheduled messages wait on the audio side, without
+        // allocating there
+        self.pending.reserve(capacity);
+        self.events.reserve(capacity);
+        let ast = self.ast.take();
+        let fixed = self
+            .index_info
+            .keys()
+            .filter(|key| {
+                !ast.as_ref()
+                    .is_some_and(|ast| ast.get().nodes.contains_key(key.as_str()))
+            })
+            .cloned()
+            .collect();
+        let handle = EngineHandle {
+            ast,
+            fixed,
+            // the audio side never builds nodes
+            samples: std::mem::take(&mut self.samples),
+            nodes: std::mem::take(&mut self.nodes),
+            param_values: std::mem::take(&mut self.param_values),
+            history: std::mem::take(&mut self.history),
+            scenes: std::mem::take(&mut self.scenes),
+            taps: self.taps.clone(),
+            bpm: self.bpm,
+            sr: self.sr,
+            channels: self.channels(),
+            seed: self.seed,
+            crossfade_ms: self.crossfade_ms,
+            fade_nodes: self.fades.iter().map(|fade| fade.old.len() + 1).sum(),
+            commands: command_tx,
+            garbage: garbage_rx,
+        };
+        let processor = EngineProcessor {
+            engine: self,
+            commands: command_rx,
+            garbage: garbage_tx,
+        };
+        (handle, processor)
+    }
+}
+
+/// The control side of a split [`Engine`]. It mirrors the program the audio side will be
+/// running once all sent changes are applied, and diffs new code against it.
+pub struct EngineHandle<const N: usize> {
+    ast: Option<YokedAst>,
+    fixed: Vec<String>,
+    samples: SampleBank,
+    nodes: NodeRegistry<N>,
+    param_values: ParamValues,
+    history: History,
+    scenes: Scenes,
+    // the taps the audio side writes
+    taps: Vec<TapWriter>,
+    bpm: f32,
+    sr: usize,
+    channels: usize,
+    seed: usize,
+    crossfade_ms: f32,
+    fade_nodes: usize,
+    commands: Producer<Command<N>>,
+    garbage: Consumer<Garbage<N>>,
+}
+
+impl<const N: usize> EngineHandle<N> {
+    /// Parses and builds `code`, then queues the change for the audio thread.
+    pub fn update_with_code(&mut self, code: &str) -> Result<(), EngineError> {
+        self.send_code(code, self.crossfade_ms, None)?;
+        self.history.push(code);
+        self.scenes.set_current(None);
+        Ok(())
+    }
+
+    /// See [`Engine::history`].
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// See [`Engine::set_history_capacity`].
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history.set_capacity(capacity);
+    }
+
+    /// See [`Engine::undo`].
+    pub fn undo(&mut self) -> Result<Option<usize>, EngineError> {
+        self.step_history(-1)
+    }
+
+    /// See [`Engine::redo`].
+    pub fn redo(&mut self) -> Result<Option<usize>, EngineError> {
+        self.step_history(1)
+    }
+
+    /// See [`Engine::revert_to`].
+    pub fn revert_to(&mut self, version: usize) -> Result<(), EngineError> {
+        let position = self
+            .history
+            .position(version)
+            .ok_or(EngineError::NonExistVersion(version))?;
+        self.go_to(position)
+    }
+
+    fn step_history(&mut self, steps: isize) -> Result<Option<usize>, EngineError> {
+        let Some(position) = self.history.step(steps) else {
+            return Ok(None);
+        };
+        self.go_to(position)?;
+        Ok(self.history.current())
+    }
+
+    fn go_to(&mut self, position: usize) -> Result<(), EngineError> {
+        let code = self.history.code_at(position).to_owned();
+        self.send_code(&code, self.crossfade_ms, None)?;
+        self.history.set_current(position);
+        self.scenes.set_current(None);
+        Ok(())
+    }
+
+    /// See [`Engine::add_scene`].
+    pub fn add_scene(&mut self, name: &str, code: &str) -> Result<(), EngineError> {
+        self.scenes.insert(name, code)
+    }
+
+    pub fn remove_scene(&mut self, name: &str) -> bool {
+        self.scenes.remove(name)
+    }
+
+    /// See [`Engine::scenes`].
+    pub fn scenes(&self) -> impl Iterator<Item = &str> {
+        self.scenes.names()
+    }
+
+    /// See [`Engine::current_scene`].
+    pub fn current_scene(&self) -> Option<&str> {
+        self.scenes.current()
+    }
+
+    /// See [`Engine::switch_scene`]. The audio thread waits for the boundary.
+    pub fn switch_scene(
+        &mut self,
+        name: &str,
+        quantize: Quantize,
+        crossfade_ms: f32,
+    ) -> Result<(), EngineError> {
+        let code = self.scenes.get(name)?.to_owned();
+        self.send_code(&code, crossfade_ms.max(0.), Some(quantize))?;
+        self.history.push(&code);
+        self.scenes.set_current(Some(name));
+        Ok(())
+    }
+
+    fn send_code(
+        &mut self,
+        code: &str,
+        crossfade_ms: f32,
+        quantize: Option<Quantize>,
+    ) -> Result<(), EngineError> {
+        let mut update = Planner {
+            ast: self.ast.as_ref().map(|ast| ast.get()),
+            fixed: self.fixed.iter().map(String::as_str).collect(),
+            samples: &self.samples,
+            nodes: &self.nodes,
+            sr: self.sr,
+            channels: self.channels,
+            bpm: self.bpm,
+            seed: self.seed,
+            crossfade_ms,
+            fade_nodes: self.fade_nodes,
+        }
+        .plan(code)?;
+        // the audio side doesn't need the AST, we keep it to diff the next change against
+        let ast = update.ast.take();
+        let fade_nodes = update.fade_nodes();
+        let mut param_values = self.param_values.clone();
+        param_values.follow(&update);
+        let update = Box::new(update);
+        self.push(match quantize {
+            Some(quantize) => Command::QuantizedUpdate(update, quantize),
+            None => Command::Update(update),
+        })?;
+        self.ast = ast;
+        self.param_values = param_values;
+        self.fade_nodes = fade_nodes;
+        Ok(())
+    }
+
+    /// Same format as [`Engine::send_msg`].
+    pub fn send_msg(&mut self, msg: &str) -> Result<(), EngineError> {
+        for (chain_name, chain_pos, message) in parse_msg(msg) {
+            let value = ParamValue::from_message(&message);
+            self.push(Command::Message(chain_name.clone(), chain_pos, message))?;
+            if let Some((index, value)) = value {
+                self.param_values
+                    .insert(&chain_name, chain_pos, index, value);
+            }
+        }
+        Ok(())
+    }
+
+    /// See [`Engine::set_param`]. The node is looked up in the last program sent.
+    pub fn set_param(
+        &mut self,
+        chain_name: &str,
+        node: NodeSelector<'_>,
+        param: ParamId<'_>,
+        value: ParamValue,
+    ) -> Result<(), EngineError> {
+        let ast = self.ast.as_ref().map(|ast| ast.get());
+        let (position, msg) =
+            param_message(ast, &self.nodes, chain_name, node, param, value.clone())?;
+        let index = ParamValue::from_message(&msg).map(|(index, _)| index);
+        self.push(Command::Message(chain_name.to_string(), position, msg))?;
+        if let Some(index) = index {
+            self.param_values.insert(chain_name, position, index, value);
+        }
+        Ok(())
+    }
+
+    /// See [`Engine::schedule`]. The node is looked up in the last program sent.
+    pub fn schedule(
+        &mut self,
+        at: EventTime,
+        chain_name: &str,
+        node: NodeSelector<'_>,
+        msg: Message,
+    ) -> Result<(), EngineError> {
+        let ast = self.ast.as_ref().map(|ast| ast.get());
+        let (position, _) = find_node(ast, chain_name, node)?;
+        self.push(Command::Schedule(at, chain_name.to_string(), position, msg))
+    }
+
+    /// See [`Engine::set_param_smoothing`].
+    pub fn set_param_smoothing(
+        &mut self,
+        chain_name: &str,
+        node: NodeSelector<'_>,
+        param: ParamId<'_>,
+        smoothing: Smoothing,
+    ) -> Result<(), EngineError> {
+        let ast = self.ast.as_ref().map(|ast| ast.get());
+        let (position, msg) =
+            smoothing_message(ast, &self.nodes, chain_name, node, param, smoothing)?;
+        self.push(Command::Message(chain_name.to_string(), position, msg))
+    }
+
+    /// See [`Engine::set_sr`]. Delay lines are reallocated on the audio thread, so this is
+    /// meant for when the audio device changes, not for while it plays.
+    pub fn set_sr(&mut self, sr: usize) -> Result<(), EngineError> {
+        self.push(Command::SetSampleRate(sr))?;
+        self.sr = sr;
+        Ok(())
+    }
+
+    /// See [`Engine::set_master`].
+    pub fn set_master(&mut self, master: MasterConfig) -> Result<(), EngineError> {
+        self.push(Command::SetMaster(master))
+    }
+
+    pub fn set_track_amp(&mut self, amp: f32) -> Result<(), EngineError> {
+        self.push(Command::SetTrackAmp(amp))
+    }
+
+    /// See [`Engine::set_smoothing`].
+    pub fn set_smoothing(&mut self, smoothing: Smoothing) -> Result<(), EngineError> {
+        self.push(Command::SetSmoothing(smoothing))
+    }
+
+    pub fn set_bpm(&mut self, bpm: f32) -> Result<(), EngineError> {
+        self.push(Command::SetBpm(bpm))?;
+        self.bpm = bpm;
+        Ok(())
+    }
+
+    /// See [`Engine::ramp_bpm`]. The ramp starts where the audio thread is when it gets it.
+    pub fn ramp_bpm(&mut self, bpm: f32, bars: f32) -> Result<(), EngineError> {
+        self.push(Command::RampBpm(bpm, bars))?;
+        self.bpm = bpm;
+        Ok(())
+    }
+
+    /// See [`Engine::set_tempo_map`]. The map replaced on the audio thread comes back to be
+    /// freed here.
+    pub fn set_tempo_map(&mut self, map: TempoMap) -> Result<(), EngineError> {
+        self.push(Command::SetTempoMap(map))
+    }
+
+    /// See [`Engine::set_seed`]. Code sent afterwards is built with the new seed.
+    pub fn set_seed(&mut self, seed: usize) -> Result<(), EngineError> {
+        self.push(Command::SetSeed(seed))?;
+        self.seed = seed;
+        Ok(())
+    }
+
+    /// See [`Engine::set_quantize`]. Updates already sent keep their boundary.
+    pub fn set_quantize(&mut self, quantize: Quantize) -> Result<(), EngineError> {
+        self.push(Command::SetQuantize(quantize))
+    }
+
+    pub fn play(&mut self) -> Result<(), EngineError> {
+        self.push(Command::SetTransport(TransportState::Playing))
+    }
+
+    pub fn pause(&mut self) -> Result<(), EngineError> {
+        self.push(Command::SetTransport(TransportState::Paused))
+    }
+
+    pub fn stop(&mut self) -> Result<(), EngineError> {
+        self.push(Command::SetTransport(TransportState::Stopped))
+    }
+
+    /// See [`Engine::seek`].
+    pub fn seek(&mut self, bar: f32) -> Result<(), EngineError> {
+        self.push(Command::Seek(bar))
+    }
+
+    /// See [`Engine::set_update_crossfade_ms`].
+    pub fn set_update_crossfade_ms(&mut self, ms: f32) {
+        self.crossfade_ms = ms.max(0.);
+    }
+
+    pub fn get_bpm(&self) -> f32 {
+        self.bpm
+    }
+
+    /// The sample rate new nodes are built for.
+    pub fn sr(&self) -> usize {
+        self.sr
+    }
+
+    /// See [`Engine::channels`].
+    pub fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// See [`Engine::add_sample`]. A replaced sample comes back to be freed here, not on the
+    /// audio thread.
+    #[cfg(feature = "use-samples")]
+    pub fn add_sample(
+        &mut self,
+        name: &str,
+        data: impl Into<Arc<[f32]>>,
+        channels: usize,
+        sr: usize,
+    ) -> Result<(), EngineError> {
+        self.collect_garbage();
+        let sample = crate::util::checked_sample(name, data.into(), channels, sr)?;
+        let Some(old) = self.samples.insert(name, sample) else {
+            return Ok(());
+        };
+        let messages = match &self.ast {
+            Some(ast) => crate::util::sample_messages(ast.get(), name, &self.samples),
+            None => vec![],
+        };
+        if messages.is_empty() {
+            return Ok(());
+        }
+        if self.commands.slots() < messages.len() + 1 {
+            self.samples.insert(name, old);
+            return Err(EngineError::QueueFull);
+        }
+        for (chain_name, chain_pos, msg) in messages {
+            self.push(Command::Message(chain_name, chain_pos, msg))?;
+        }
+        self.push(Command::Retire(old))
+    }
+
+    /// See [`Engine::remove_sample`].
+    #[cfg(feature = "use-samples")]
+    pub fn remove_sample(&mut self, name: &str) -> Option<Sample> {
+        self.samples.remove(name)
+    }
+
+    pub fn samples(&self) -> &SampleBank {
+        &self.samples
+    }
+
+    /// See [`Engine::register_node`]. Code that is already running keeps the nodes it has.
+    pub fn register_node<F>(&mut self, name: &str, params: &'static [ParamInfo], build: F)
+    where
+        F: Fn(&[NodeArg], usize, f32) -> GlicolNodeData<N> + Send + Sync + 'static,
+    {
+        self.nodes.insert(name, params, build);
+    }
+
+    /// The number of nodes in the current program.
+    pub fn node_count(&self) -> usize {
+        self.ast
+            .as_ref()
+            .map_or(0, |ast| ast.get().nodes.values().map(Vec::len).sum())
+    }
+
+    /// See [`Engine::describe`]. Describes the last program sent, which the audio thread
+    /// may not be running yet.
+    pub fn describe(&self) -> GraphDescription {
+        let fixed: Vec<&str> = self.fixed.iter().map(String::as_str).collect();
+        describe(
+            self.ast.as_ref().map(|ast| ast.get()),
+            &fixed,
+            &self.param_values,
+            &self.nodes,
+        )
+    }
+
+    /// Drops everything the audio thread has handed back. Called before every change is
+    /// sent, but can be called periodically when nothing is sent for a while.
+    pub fn collect_garbage(&mut self) {
+        while self.garbage.pop().is_ok() {}
+    }
+
+    // the audio side swaps its taps for a new list, leaving out the dropped ones
+    pub(crate) fn add_tap(&mut self, tap: TapWriter) -> Result<(), EngineError> {
+        let mut taps: Vec<TapWriter> = self
+            .taps
+            .iter()
+            .filter(|tap| !tap.is_dropped())
+            .cloned()
+            .collect();
+        taps.push(tap);
+        self.push(Command::SetTaps(taps.clone()))?;
+        self.taps = taps;
+        Ok(())
+    }
+
+    // the audio side stops taking commands once it can't hand back what they replace
+    fn push(&mut self, command: Command<N>) -> Result<(), EngineError> {
+        self.collect_garbage();
+        self.commands
+            .push(command)
+            .map_err(|_| EngineError::QueueFull)
+    }
+}
+
+/// The audio side of a split [`Engine`]: call [`EngineProcessor::next_block`] from the
+/// audio callback.
+pub struct EngineProcessor<const N: usize> {
+    engine: Engine<N>,
+    commands: Consumer<Command<N>>,
+    garbage: Producer<Garbage<N>>,
+}
+
+impl<const N: usize> EngineProcessor<N> {
+    /// Applies all pending changes that are due, then renders the next block.
+    pub fn next_block(&mut self, buf: &[&[f32]]) -> &[Buffer<N>] {
+        while let Ok(command) = self.commands.peek() {
+            // leave updates and scheduled messages in the queue once there is no room left
+            // to keep them waiting
+            let full = match command {
+                Command::Update(_) | Command::QuantizedUpdate(..) => {
+                    self.engine.pending.len() == self.engine.pending.capacity()
+                }
+                Command::Schedule(..) => self.engine.events.len() == self.engine.events.capacity(),
+                _ => false,
+            };
+            // and once the garbage queue could not take back what this command and the
+            // waiting ones hand over, until the handle collects it
+            let waiting = self.engine.pending.len() + self.engine.events.len();
+            if full || self.garbage.slots() <= waiting {
+                break;
+            }
+            let Ok(command) = self.commands.pop() else {
+                break;
+            };
+            let garbage = match command {
+                Command::Update(update) => match self.engine.schedule_update(update) {
+                    Some(update) => Garbage::Update(update),
+                    None => continue,
+                },
+                Command::QuantizedUpdate(update, quantize) => {
+                    match self.engine.schedule_update_with(update, quantize) {
+                        Some(update) => Garbage::Update(update),
+                        None => continue,
+                    }
+                }
+                Command::SetBpm(bpm) => {
+                    let tempo = self.engine.replace_tempo(Tempo::Fixed);
+                    self.engine.change_bpm(bpm);
+                    Garbage::Tempo(tempo)
+                }
+                Command::RampBpm(bpm, bars) => {
+                    Garbage::Tempo(self.engine.replace_tempo(self.engine.ramp(bpm, bars)))
+                }
+                Command::SetTempoMap(map) => {
+                    Garbage::Tempo(self.engine.replace_tempo(Tempo::Map(map)))
+                }
+                Command::SetQuantize(quantize) => {
+                    self.engine.set_quantize(quantize);
+                    continue;
+                }
+                Command::SetTransport(state) => {
+                    match state {
+                        TransportState::Playing => self.engine.play(),
+                        TransportState::Paused => self.engine.pause(),
+                        TransportState::Stopped => self.engine.stop(),
+                    }
+                    continue;
+                }
+                Command::Seek(bar) => {
+                    self.engine.seek(bar);
+                    continue;
+                }
+                Command::SetSmoothing(smoothing) => {
+                    self.engine.set_smoothing(smoothing);
+                    continue;
+                }
+                Command::SetSeed(seed) => {
+                    self.engine.set_seed(seed);
+                    continue;
+                }
+                Command::SetSampleRate(sr) => {
+                    self.engine.set_sr(sr);
+                    continue;
+                }
+                Command::SetMaster(master) => {
+                    self.engine.set_master(master);
+                    continue;
+                }
+                Command::SetTrackAmp(amp) => {
+                    self.engine.set_track_amp(amp);
+                    continue;
+                }
+                Command::Message(chain_name, chain_pos, message) => {
+                    self.engine.send_msg_to(&chain_name, chain_pos, message);
+                    Garbage::Name(chain_name)
+                }
+                Command::Schedule(at, chain_name, chain_pos, message) => {
+                    let due = self.engine.event_due(at);
+                    self.engine
+                        .insert_event((due, chain_name, chain_pos, message));
+                    continue;
+                }
+                Command::SetTaps(taps) => {
+                    Garbage::Taps(std::mem::replace(&mut self.engine.taps, taps))
+                }
+                #[cfg(feature = "use-samples")]
+                Command::Retire(sample) => Garbage::Sample(sample),
+            };
+            // there is room for it, so this only fails if the handle is gone
+            let _ = self.garbage.push(garbage);
+        }
+        let garbage = &mut self.garbage;
+        self.engine.apply_due(|update| {
+            let _ = garbage.push(Garbage::Update(update));
+        });
+        self.engine.render_block(buf, |chain_name| {
+            let _ = garbage.push(Garbage::Name(chain_name));
+        })
+    }
+}
@@ -0,0 +1,123 @@
+//! Nodes a host adds to the language, written in the code like the built-in ones.
+
+use std::sync::Arc;
+
+use glicol_parser::nodes::{Argument, Component, Custom};
+use glicol_synth::{ParamInfo, ParamKind};
+use hashbrown::HashMap;
+
+use crate::{param::component_params, Engine, EngineError, GlicolNodeData};
+
+/// An argument of a registered node, as written in the code.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeArg {
+    Number(f32),
+    /// As written, like `\low`, the way the built-in nodes get their symbols.
+    Symbol(String),
+    /// A chain driving the parameter, like `~throttle`. The chains are connected as the
+    /// inputs of the node, in the order of the arguments.
+    Reference(String),
+}
+
+type Build<const N: usize> = dyn Fn(&[NodeArg], usize, f32) -> GlicolNodeData<N> + Send + Sync;
+
+struct CustomNode<const N: usize> {
+    params: &'static [ParamInfo],
+    build: Box<Build<N>>,
+}
+
+/// The nodes registered with [`Engine::register_node`], by name.
+#[derive(Clone, Default)]
+pub struct NodeRegistry<const N: usize>(HashMap<String, Arc<CustomNode<N>>>);
+
+impl<const N: usize> NodeRegistry<N> {
+    pub(crate) fn insert<F>(&mut self, name: &str, params: &'static [ParamInfo], build: F)
+    where
+        F: Fn(&[NodeArg], usize, f32) -> GlicolNodeData<N> + Send + Sync + 'static,
+    {
+        let node = CustomNode {
+            params,
+            build: Box::new(build),
+        };
+        self.0.insert(name.to_string(), Arc::new(node));
+    }
+
+    /// Like [`component_params`], knowing the registered nodes.
+    pub(crate) fn params(&self, component: &Component<'_>) -> &'static [ParamInfo] {
+        match component {
+            Component::Custom(custom) => match self.0.get(custom.name) {
+                Some(node) => node.params,
+                None => &[],
+            },
+            _ => component_params::<N>(component),
+        }
+    }
+
+    /// Builds a registered node, with the chains it reads.
+    pub(crate) fn build(
+        &self,
+        custom: &Custom<'_>,
+        sr: usize,
+        bpm: f32,
+    ) -> Result<(GlicolNodeData<N>, Vec<String>), EngineError> {
+        let name = custom.name;
+        let node = self
+            .0
+            .get(name)
+            .ok_or_else(|| EngineError::NonExistNode(name.to_string()))?;
+        if custom.args.len() != node.params.len() {
+            return Err(EngineError::InvalidParamValue(format!(
+                "{name} takes {} arguments, not {}",
+                node.params.len(),
+                custom.args.len()
+            )));
+        }
+
+        let mut args = Vec::with_capacity(custom.args.len());
+        let mut reflist = vec![];
+        for (i, arg) in custom.args.iter().enumerate() {
+            let info = node
+                .params
+                .iter()
+                .find(|info| info.index as usize == i)
+                .ok_or_else(|| EngineError::NonExistParam(format!("Index({i}) of {name}")))?;
+            let takes = match arg {
+                Argument::Number(_) => info.kind == ParamKind::Number,
+                Argument::Symbol(_) => info.kind == ParamKind::Symbol,
+                Argument::Ref(_) => info.accepts_ref,
+            };
+            if !takes {
+                return Err(EngineError::InvalidParamValue(format!(
+                    "{} of {name} takes a {:?}, not {arg:?}",
+                    info.name, info.kind
+                )));
+            }
+            args.push(match arg {
+                Argument::Number(v) => NodeArg::Number(*v),
+                Argument::Symbol(s) => NodeArg::Symbol(s.to_string()),
+                Argument::Ref(r) => {
+                    reflist.push(r.to_string());
+                    NodeArg::Reference(r.to_string())
+                }
+            });
+        }
+        Ok(((node.build)(&args, sr, bpm), reflist))
+    }
+}
+
+impl<const N: usize> Engine<N> {
+    /// Adds a node to the language, for code like `rumble 40 \low ~throttle`. The arguments
+    /// set the parameters in `params` in the order of their index, so there must be one for
+    /// each, and a chain only goes where `accepts_ref` is set. `build` makes the node from
+    /// them, the sample rate and the bpm; later changes to the code send it the same
+    /// `Message::SetTo*` as `set_param` would.
+    ///
+    /// The name of a built-in node keeps meaning the built-in node, and a registered node
+    /// needs at least one argument, as a bare name reads another chain.
+    pub fn register_node<F>(&mut self, name: &str, params: &'static [ParamInfo], build: F)
+    where
+        F: Fn(&[NodeArg], usize, f32) -> GlicolNodeData<N> + Send + Sync + 'static,
+    {
+        self.nodes.insert(name, params, build);
+    }
+}
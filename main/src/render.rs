@@ -0,0 +1,55 @@
+use crate::Engine;
+
+impl<const N: usize> Engine<N> {
+    /// Renders `duration` seconds of audio without an audio device, by driving
+    /// `next_block` until enough frames are produced.
+    /// The result is interleaved, with one sample per output channel per frame.
+    /// Back-to-back calls line up: frames of the last block that were not returned come
+    /// first in the next call, unless `next_block` is called or the graph, a parameter or
+    /// the transport is changed in between.
+    pub fn render(&mut self, duration: f32) -> Vec<f32> {
+        let frames = (duration.max(0.) * self.sr as f32).round() as usize;
+        let channels = self.channels();
+        if channels == 0 {
+            return vec![];
+        }
+        let mut output = Vec::with_capacity(frames * channels);
+        let mut rest = std::mem::take(&mut self.render_rest);
+        let kept = rest.len().min(frames * channels);
+        output.extend(rest.drain(..kept));
+        let mut rendered = kept / channels;
+        while rendered < frames {
+            let block = self.render_block(&[], drop);
+            let len = N.min(frames - rendered);
+            for i in 0..len {
+                output.extend(block.iter().map(|buf| buf[i]));
+            }
+            for i in len..N {
+                rest.extend(block.iter().map(|buf| buf[i]));
+            }
+            rendered += len;
+        }
+        self.render_rest = rest;
+        output
+    }
+
+    /// Renders `duration` seconds of audio and writes it to a 32-bit float WAV file.
+    #[cfg(feature = "render-wav")]
+    pub fn render_to_wav(
+        &mut self,
+        path: impl AsRef<std::path::Path>,
+        duration: f32,
+    ) -> Result<(), hound::Error> {
+        let spec = hound::WavSpec {
+            channels: self.channels() as u16,
+            sample_rate: self.sr as u32,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut writer = hound::WavWriter::create(path, spec)?;
+        for sample in self.render(duration) {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()
+    }
+}
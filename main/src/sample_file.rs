@@ -0,0 +1,178 @@
+//! Loading samples from WAV, FLAC and Ogg Vorbis files.
+
+use std::{fs::File, path::Path};
+
+use glicol_synth::Sample;
+use symphonia::core::{
+    audio::SampleBuffer, codecs::DecoderOptions, errors::Error as DecodeError,
+    formats::FormatOptions, io::MediaSourceStream, meta::MetadataOptions, probe::Hint,
+};
+
+use crate::{realtime::EngineHandle, Engine, EngineError};
+
+const EXTENSIONS: [&str; 4] = ["wav", "flac", "ogg", "oga"];
+
+/// Decodes an audio file into a sample at `sr`. Files with more than two channels keep
+/// the first two, as the samplers play mono or stereo.
+pub fn decode_sample_file(path: impl AsRef<Path>, sr: usize) -> Result<Sample, EngineError> {
+    let path = path.as_ref();
+    let error =
+        |err: &dyn std::fmt::Display| EngineError::SampleFile(format!("{}: {err}", path.display()));
+    let (interleaved, channels, rate) = decode(path).map_err(|err| error(&err))?;
+    if channels == 0 || rate == 0 || interleaved.is_empty() {
+        return Err(error(&"no audio"));
+    }
+
+    let kept = channels.min(2);
+    let data: Vec<f32> = (0..kept)
+        .flat_map(|c| {
+            let channel: Vec<f32> = interleaved
+                .iter()
+                .skip(c)
+                .step_by(channels)
+                .copied()
+                .collect();
+            resample(&channel, rate, sr)
+        })
+        .collect();
+    Ok(Sample::new(data, kept, sr))
+}
+
+// the sample name for a file, like `\808bd` for `808bd.wav`
+fn sample_name(path: &Path) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    if !EXTENSIONS.contains(&extension.as_str()) {
+        return None;
+    }
+    Some(format!("\\{}", path.file_stem()?.to_str()?))
+}
+
+// every audio file in `dir` with its sample name, sorted by name
+fn sample_files(dir: &Path) -> Result<Vec<(String, std::path::PathBuf)>, EngineError> {
+    let error = |err: std::io::Error| EngineError::SampleFile(format!("{}: {err}", dir.display()));
+    let mut files = vec![];
+    for entry in std::fs::read_dir(dir).map_err(error)? {
+        let path = entry.map_err(error)?.path();
+        if let Some(name) = path.is_file().then(|| sample_name(&path)).flatten() {
+            files.push((name, path));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// interleaved samples, channels and sample rate
+fn decode(path: &Path) -> Result<(Vec<f32>, usize, usize), DecodeError> {
+    let source = MediaSourceStream::new(Box::new(File::open(path)?), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    let mut format = symphonia::default::get_probe()
+        .format(
+            &hint,
+            source,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )?
+        .format;
+    let track = format
+        .default_track()
+        .ok_or(DecodeError::Unsupported("a file without audio"))?;
+    let track_id = track.id;
+    let mut decoder =
+        symphonia::default::get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut samples = vec![];
+    let (mut channels, mut rate) = (0, 0);
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(DecodeError::IoError(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Err(err) => return Err(err),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // a corrupt packet is skipped
+            Err(DecodeError::DecodeError(_)) => continue,
+            Err(err) => return Err(err),
+        };
+        let spec = *decoded.spec();
+        channels = spec.channels.count();
+        rate = spec.rate as usize;
+        let mut buffer = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buffer.copy_interleaved_ref(decoded);
+        samples.extend_from_slice(buffer.samples());
+    }
+    Ok((samples, channels, rate))
+}
+
+// cubic interpolation between the samples around each new position
+fn resample(channel: &[f32], from: usize, to: usize) -> Vec<f32> {
+    if from == to {
+        return channel.to_vec();
+    }
+    let last = channel.len() as isize - 1;
+    let at = |i: isize| channel[i.clamp(0, last) as usize];
+    let len = (channel.len() as f64 * to as f64 / from as f64).round() as usize;
+    (0..len)
+        .map(|i| {
+            let pos = i as f64 * from as f64 / to as f64;
+            let (index, t) = (pos.floor() as isize, pos.fract() as f32);
+            let (x0, x1, x2, x3) = (at(index - 1), at(index), at(index + 1), at(index + 2));
+            0.5 * (2. * x1
+                + (x2 - x0) * t
+                + (2. * x0 - 5. * x1 + 4. * x2 - x3) * t * t
+                + (3. * x1 - x0 - 3. * x2 + x3) * t * t * t)
+        })
+        .collect()
+}
+
+impl<const N: usize> Engine<N> {
+    /// Decodes a WAV, FLAC or Ogg Vorbis file and adds it as `name`, resampled to the
+    /// engine's rate.
+    pub fn load_sample_file(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(), EngineError> {
+        let sample = decode_sample_file(path, self.sr)?;
+        self.add_sample(name, sample.data, sample.channels, sample.sr)
+    }
+
+    /// Loads every audio file in `dir`, naming each after its file stem: `808bd.wav`
+    /// becomes `\808bd`. Returns the names, sorted.
+    pub fn load_sample_dir(&mut self, dir: impl AsRef<Path>) -> Result<Vec<String>, EngineError> {
+        let files = sample_files(dir.as_ref())?;
+        for (name, path) in &files {
+            self.load_sample_file(name, path)?;
+        }
+        Ok(files.into_iter().map(|(name, _)| name).collect())
+    }
+}
+
+impl<const N: usize> EngineHandle<N> {
+    /// See [`Engine::load_sample_file`].
+    pub fn load_sample_file(
+        &mut self,
+        name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<(), EngineError> {
+        let sample = decode_sample_file(path, self.sr())?;
+        self.add_sample(name, sample.data, sample.channels, sample.sr)
+    }
+
+    /// See [`Engine::load_sample_dir`].
+    pub fn load_sample_dir(&mut self, dir: impl AsRef<Path>) -> Result<Vec<String>, EngineError> {
+        let files = sample_files(dir.as_ref())?;
+        for (name, path) in &files {
+            self.load_sample_file(name, path)?;
+        }
+        Ok(files.into_iter().map(|(name, _)| name).collect())
+    }
+}
@@ -0,0 +1,89 @@
+use glicol_parser::get_ast;
+use hashbrown::HashMap;
+
+use crate::{Engine, EngineError, Quantize};
+
+/// The programs added with [`Engine::add_scene`], by name.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Scenes {
+    scenes: HashMap<String, Box<str>>,
+    // the scene switched to last, until other code is sent
+    current: Option<String>,
+}
+
+impl Scenes {
+    /// Checks that `code` parses before keeping it.
+    pub fn insert(&mut self, name: &str, code: &str) -> Result<(), EngineError> {
+        get_ast(code)?;
+        self.scenes
+            .insert(name.to_string(), code.to_owned().into_boxed_str());
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> bool {
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+        self.scenes.remove(name).is_some()
+    }
+
+    pub fn get(&self, name: &str) -> Result<&str, EngineError> {
+        self.scenes
+            .get(name)
+            .map(|code| &**code)
+            .ok_or_else(|| EngineError::NonExistScene(name.to_string()))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scenes.keys().map(String::as_str)
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    pub fn set_current(&mut self, name: Option<&str>) {
+        self.current = name.map(ToString::to_string);
+    }
+}
+
+impl<const N: usize> Engine<N> {
+    /// Keeps `code` as a scene to switch to with [`Engine::switch_scene`], replacing any
+    /// scene of the same name. The code is parsed now, but only built when switched to.
+    pub fn add_scene(&mut self, name: &str, code: &str) -> Result<(), EngineError> {
+        self.scenes.insert(name, code)
+    }
+
+    pub fn remove_scene(&mut self, name: &str) -> bool {
+        self.scenes.remove(name)
+    }
+
+    /// The names of the scenes, in no particular order.
+    pub fn scenes(&self) -> impl Iterator<Item = &str> {
+        self.scenes.names()
+    }
+
+    /// The scene switched to last, or `None` once other code was sent.
+    pub fn current_scene(&self) -> Option<&str> {
+        self.scenes.current()
+    }
+
+    /// Switches to a scene at the next `quantize` boundary, even when not livecoding,
+    /// fading changed nodes over `crossfade_ms`. It is diffed against the program like any
+    /// update, so chains the scenes share keep playing as they are.
+    pub fn switch_scene(
+        &mut self,
+        name: &str,
+        quantize: Quantize,
+        crossfade_ms: f32,
+    ) -> Result<(), EngineError> {
+        let code = self.scenes.get(name)?;
+        let mut planner = self.planner();
+        planner.crossfade_ms = crossfade_ms.max(0.);
+        let update = planner.plan(code)?;
+        self.history.push(code);
+        self.schedule_update_with(Box::new(update), quantize);
+        self.scenes.set_current(Some(name));
+        Ok(())
+    }
+}
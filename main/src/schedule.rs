@@ -0,0 +1,75 @@
+use glicol_synth::Message;
+
+use crate::{param::find_node, Engine, EngineError, NodeSelector};
+
+/// When a message from [`Engine::schedule`] is due, on the transport.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventTime {
+    /// In samples at the current tempo, like [`TransportPosition::samples`](crate::TransportPosition::samples).
+    Samples(usize),
+    /// In beats from the start of the song.
+    Beats(f64),
+}
+
+// (due beat, chain, position in chain, message)
+pub(crate) type Event = (f64, String, usize, Message);
+
+impl<const N: usize> Engine<N> {
+    /// Sends `msg` to a node when the transport reaches `at`. Nodes that can act within a
+    /// block take it on that very sample, like `Message::Note` for `msgsynth` or
+    /// `Message::Trigger` for `sp`; the rest at the start of its block.
+    /// Times already passed are sent with the next block.
+    pub fn schedule(
+        &mut self,
+        at: EventTime,
+        chain_name: &str,
+        node: NodeSelector<'_>,
+        msg: Message,
+    ) -> Result<(), EngineError> {
+        let ast = self.ast.as_ref().map(|ast| ast.get());
+        let (position, _) = find_node(ast, chain_name, node)?;
+        let due = self.event_due(at);
+        self.insert_event((due, chain_name.to_string(), position, msg));
+        Ok(())
+    }
+
+    /// How many scheduled messages are waiting to be sent.
+    pub fn scheduled_len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub(crate) fn event_due(&self, at: EventTime) -> f64 {
+        match at {
+            EventTime::Samples(samples) => self.beats_at(samples),
+            EventTime::Beats(beats) => beats.max(0.),
+        }
+    }
+
+    // after the events due at the same beat, so they are sent in the order scheduled
+    pub(crate) fn insert_event(&mut self, event: Event) {
+        let at = self.events.partition_point(|(due, ..)| *due <= event.0);
+        self.events.insert(at, event);
+    }
+
+    /// Sends the messages due in the coming block with their offset into it,
+    /// handing each chain name to `done` afterwards.
+    pub(crate) fn send_due_events(&mut self, mut done: impl FnMut(String)) {
+        while let Some((due, ..)) = self.events.front() {
+            let due = self.samples_at(*due);
+            if due >= self.clock + N {
+                break;
+            }
+            if let Some((_, chain_name, position, msg)) = self.events.pop_front() {
+                if let Some(index) = self
+                    .index_info
+                    .get(&chain_name)
+                    .and_then(|chain| chain.get(position))
+                {
+                    let offset = due.saturating_sub(self.clock);
+                    self.context.graph[*index].node.send_msg_at(offset, msg);
+                }
+                done(chain_name);
+            }
+        }
+    }
+}
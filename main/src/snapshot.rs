@@ -0,0 +1,116 @@
+use glicol_synth::Message;
+
+use crate::{Engine, EngineError, ParamValue, Tempo, TransportState};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The state of a session that code alone doesn't hold, made by [`Engine::snapshot`]
+/// and brought back with [`Engine::restore`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    /// The running program.
+    pub code: String,
+    pub bpm: f32,
+    /// Where the tempo goes from `bpm`, when it ramps or follows a map.
+    pub tempo: Tempo,
+    pub seed: usize,
+    pub track_amp: f32,
+    /// Parameters changed by messages since the code set them.
+    pub params: Vec<ParamOverride>,
+    /// The transport position in samples at `sr`.
+    pub position: usize,
+    /// The same position in beats, which it is restored from.
+    pub beats: f64,
+    pub sr: usize,
+    pub transport: TransportState,
+}
+
+/// A parameter set with [`Engine::send_msg`] or [`Engine::set_param`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParamOverride {
+    pub chain: String,
+    /// The position of the node in the chain, counted from 0.
+    pub position: usize,
+    pub index: u8,
+    pub value: ParamValue,
+}
+
+impl<const N: usize> Engine<N> {
+    /// Captures the running program with everything changed on it since it was sent.
+    /// Updates still waiting for their boundary are left out.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            code: self
+                .ast
+                .as_ref()
+                .map(|ast| ast.backing_cart().to_string())
+                .unwrap_or_default(),
+            bpm: self.bpm,
+            tempo: self.tempo.clone(),
+            seed: self.seed,
+            track_amp: self.track_amp,
+            params: self.param_values.overrides(),
+            position: self.clock,
+            beats: self.beats,
+            sr: self.sr,
+            transport: self.transport,
+        }
+    }
+
+    /// Brings back a [`Snapshot`], rebuilding every node of its program right away instead
+    /// of diffing against the running one, and dropping updates still waiting. The position
+    /// is kept in time if the sample rate changed. On error nothing is changed.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), EngineError> {
+        let mut planner = self.planner();
+        planner.ast = None;
+        planner.bpm = snapshot.bpm;
+        planner.seed = snapshot.seed;
+        planner.crossfade_ms = 0.;
+        let mut update = planner.plan(&snapshot.code)?;
+
+        self.pending.clear();
+        self.events.clear();
+        self.render_rest.clear();
+        if let Some(ast) = self.ast.take() {
+            for chain_name in ast.get().nodes.keys() {
+                for index in self.index_info.remove(*chain_name).into_iter().flatten() {
+                    if let Some(nodedata) = self.context.graph.remove_node(index) {
+                        update.garbage.push(nodedata);
+                    }
+                }
+            }
+        }
+        self.refs.clear();
+        self.feedback.clear();
+        self.param_values.clear();
+
+        self.set_bpm(snapshot.bpm);
+        self.seed = snapshot.seed;
+        self.set_track_amp(snapshot.track_amp);
+        self.beats = snapshot.beats;
+        self.clock = self.samples_at(self.beats);
+        self.context
+            .send_msg_to_all(Message::SetPosition(self.clock));
+        // after `set_bpm`, which stops any ramp or map
+        self.tempo = snapshot.tempo.clone();
+        self.transport = snapshot.transport;
+        self.apply(&mut update);
+        self.history.push(&snapshot.code);
+        self.scenes.set_current(None);
+
+        for param in &snapshot.params {
+            let msg = param.value.clone().into_message(param.index);
+            self.param_values.insert(
+                &param.chain,
+                param.position,
+                param.index,
+                param.value.clone(),
+            );
+            self.send_msg_to(&param.chain, param.position, msg);
+        }
+        Ok(())
+    }
+}
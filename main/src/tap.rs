@@ -0,0 +1,193 @@
+//! Levels and recent samples of a chain, written by the audio thread and read from anywhere.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+    Arc,
+};
+
+use glicol_synth::Buffer;
+
+use crate::{realtime::EngineHandle, Engine, EngineError};
+
+// the RMS follows the signal over about this many seconds, like a VU meter
+const RMS_TIME: f32 = 0.3;
+
+// shared between a `Tap` and the engine; floats are stored as their bits
+#[derive(Debug)]
+struct TapState {
+    // one per output channel of the engine
+    peak: Box<[AtomicU32]>,
+    rms: Box<[AtomicU32]>,
+    // interleaved, `peak.len()` samples per frame
+    frames: Box<[AtomicU32]>,
+    // frames written so far, the next one goes to `written % frames.len()`
+    written: AtomicUsize,
+    // set when the `Tap` is dropped, so the engine stops writing
+    dropped: AtomicBool,
+}
+
+/// Reads the output of a chain while it plays, for meters, scopes and visuals. Made by
+/// [`Engine::tap`]. It reads every output channel of the engine, and what the chain plays on
+/// each: mono chains read the same on the first two, and chains ending in `out` read on the
+/// channels they go to.
+#[derive(Debug)]
+pub struct Tap {
+    chain: String,
+    state: Arc<TapState>,
+}
+
+impl Tap {
+    /// How many of the latest frames [`Tap::snapshot`] can return.
+    pub const FRAMES: usize = 8192;
+
+    pub fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    /// How many channels it reads, the output channels of the engine it was made by.
+    pub fn channels(&self) -> usize {
+        self.state.peak.len()
+    }
+
+    /// The loudest sample of each channel since the last call.
+    pub fn peak(&self) -> Vec<f32> {
+        self.state
+            .peak
+            .iter()
+            .map(|peak| f32::from_bits(peak.swap(0, Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// The RMS level of each channel over the last 300ms or so.
+    pub fn rms(&self) -> Vec<f32> {
+        self.state
+            .rms
+            .iter()
+            .map(|rms| f32::from_bits(rms.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// The latest `frames` samples (at most [`Tap::FRAMES`]) of each channel, oldest first.
+    /// The audio thread doesn't wait for the read, so the oldest few may already be newer
+    /// ones when it's slow.
+    pub fn snapshot(&self, frames: usize) -> Vec<Vec<f32>> {
+        let state = &self.state;
+        let channels = self.channels();
+        let written = state.written.load(Ordering::Acquire);
+        let range = written.saturating_sub(frames.min(Tap::FRAMES))..written;
+        (0..channels)
+            .map(|c| {
+                range
+                    .clone()
+                    .map(|i| {
+                        let sample = &state.frames[(i % Tap::FRAMES) * channels + c];
+                        f32::from_bits(sample.load(Ordering::Relaxed))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Drop for Tap {
+    fn drop(&mut self) {
+        self.state.dropped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The audio side of a [`Tap`].
+#[derive(Debug, Clone)]
+pub(crate) struct TapWriter {
+    pub chain: String,
+    state: Arc<TapState>,
+    mean_square: Box<[f32]>,
+}
+
+impl TapWriter {
+    pub fn new(chain: &str, channels: usize) -> (Tap, Self) {
+        let atomics = |len| (0..len).map(|_| AtomicU32::new(0)).collect();
+        let state = Arc::new(TapState {
+            peak: atomics(channels),
+            rms: atomics(channels),
+            frames: atomics(Tap::FRAMES * channels),
+            written: AtomicUsize::new(0),
+            dropped: AtomicBool::new(false),
+        });
+        let tap = Tap {
+            chain: chain.to_string(),
+            state: state.clone(),
+        };
+        let writer = Self {
+            chain: chain.to_string(),
+            state,
+            mean_square: vec![0.; channels].into_boxed_slice(),
+        };
+        (tap, writer)
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.state.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Publishes a block of the chain's output.
+    pub fn write<const N: usize>(&mut self, buffers: &[Buffer<N>], sr: usize) {
+        let state = &self.state;
+        let channels = state.peak.len();
+        // what the chain plays on output channel `c`, like the engine routes it
+        let channel = |c: usize| match buffers {
+            [mono] if c < 2 => mono,
+            _ => buffers.get(c).unwrap_or(&Buffer::SILENT),
+        };
+
+        let written = state.written.load(Ordering::Relaxed);
+        for i in 0..N {
+            let frame = (written + i) % Tap::FRAMES * channels;
+            for c in 0..channels {
+                state.frames[frame + c].store(channel(c)[i].to_bits(), Ordering::Relaxed);
+            }
+        }
+        state.written.store(written + N, Ordering::Release);
+
+        let coef = 1. - (-(N as f32) / (RMS_TIME * sr as f32)).exp();
+        for c in 0..channels {
+            let channel = channel(c);
+            let peak = channel.iter().fold(0f32, |peak, s| peak.max(s.abs()));
+            // the bits of positive floats sort like the floats
+            state.peak[c].fetch_max(peak.to_bits(), Ordering::Relaxed);
+            let block = channel.iter().map(|s| s * s).sum::<f32>() / N as f32;
+            self.mean_square[c] += (block - self.mean_square[c]) * coef;
+            state.rms[c].store(self.mean_square[c].sqrt().to_bits(), Ordering::Relaxed);
+        }
+    }
+}
+
+impl<const N: usize> Engine<N> {
+    /// Starts reading the output of `chain`, which doesn't have to exist yet. A chain that
+    /// isn't in the program reads silent. The engine stops writing once the tap is dropped.
+    pub fn tap(&mut self, chain: &str) -> Tap {
+        self.taps.retain(|tap| !tap.is_dropped());
+        let (tap, writer) = TapWriter::new(chain, self.channels());
+        self.taps.push(writer);
+        tap
+    }
+
+    // after a block is rendered
+    pub(crate) fn write_taps(&mut self) {
+        for tap in self.taps.iter_mut().filter(|tap| !tap.is_dropped()) {
+            let buffers = match self.index_info.get(&tap.chain).and_then(|c| c.last()) {
+                Some(index) => &self.context.graph[*index].buffers[..],
+                None => &[Buffer::SILENT],
+            };
+            tap.write(buffers, self.sr);
+        }
+    }
+}
+
+impl<const N: usize> EngineHandle<N> {
+    /// See [`Engine::tap`].
+    pub fn tap(&mut self, chain: &str) -> Result<Tap, EngineError> {
+        let (tap, writer) = TapWriter::new(chain, self.channels());
+        self.add_tap(writer)?;
+        Ok(tap)
+    }
+}
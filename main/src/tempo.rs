@@ -0,0 +1,144 @@
+use glicol_synth::Message;
+
+use crate::Engine;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How the tempo moves through the song, by bar, for [`Engine::set_tempo_map`].
+/// Before its first point the tempo is that of the first point, after the last it stays.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TempoMap {
+    // (bar, bpm, whether it is reached by a ramp from the point before), sorted by bar
+    points: Vec<(f64, f32, bool)>,
+}
+
+impl TempoMap {
+    /// A map starting at `bpm` on bar 0.
+    pub fn new(bpm: f32) -> Self {
+        Self {
+            points: vec![(0., bpm, false)],
+        }
+    }
+
+    /// Changes to `bpm` at the start of `bar`, counted from 0.
+    pub fn jump(self, bar: f32, bpm: f32) -> Self {
+        self.point(bar, bpm, false)
+    }
+
+    /// Moves from the tempo of the point before to `bpm` at a steady rate per bar,
+    /// reaching it at the start of `bar`.
+    pub fn ramp(self, bar: f32, bpm: f32) -> Self {
+        self.point(bar, bpm, true)
+    }
+
+    fn point(mut self, bar: f32, bpm: f32, ramp: bool) -> Self {
+        let bar = bar.max(0.) as f64;
+        let at = self.points.partition_point(|(b, _, _)| *b <= bar);
+        self.points.insert(at, (bar, bpm.max(1.), ramp));
+        self
+    }
+
+    pub fn bpm_at(&self, bar: f64) -> f32 {
+        let at = self.points.partition_point(|(b, _, _)| *b <= bar);
+        let Some(&(start, from, _)) = at.checked_sub(1).and_then(|i| self.points.get(i)) else {
+            return self.points.first().map_or(120., |(_, bpm, _)| *bpm);
+        };
+        match self.points.get(at) {
+            Some(&(end, to, true)) => from + (to - from) * ((bar - start) / (end - start)) as f32,
+            _ => from,
+        }
+    }
+}
+
+/// Where the tempo comes from while playing, kept in a [`Snapshot`](crate::Snapshot).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Tempo {
+    /// Only changed with [`Engine::set_bpm`].
+    Fixed,
+    /// A ramp from [`Engine::ramp_bpm`].
+    Ramp {
+        from: f32,
+        to: f32,
+        start_bar: f64,
+        bars: f64,
+    },
+    /// A map from [`Engine::set_tempo_map`].
+    Map(TempoMap),
+}
+
+impl<const N: usize> Engine<N> {
+    /// Moves the tempo from where it is to `bpm` over the next `bars`, at a steady rate per
+    /// bar. Replaces the tempo map; [`Engine::set_bpm`] stops the ramp.
+    pub fn ramp_bpm(&mut self, bpm: f32, bars: f32) {
+        self.replace_tempo(self.ramp(bpm, bars));
+    }
+
+    /// Makes the tempo follow `map` as the song plays, e.g. for rendering a piece with
+    /// tempo changes. [`Engine::set_bpm`] stops following it.
+    pub fn set_tempo_map(&mut self, map: TempoMap) {
+        self.replace_tempo(Tempo::Map(map));
+    }
+
+    pub fn tempo_map(&self) -> Option<&TempoMap> {
+        match &self.tempo {
+            Tempo::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn ramp(&self, bpm: f32, bars: f32) -> Tempo {
+        Tempo::Ramp {
+            from: self.bpm,
+            to: bpm.max(1.),
+            start_bar: self.position().bars,
+            bars: bars.max(0.) as f64,
+        }
+    }
+
+    /// Returns the tempo it replaces, to be dropped elsewhere when it holds a map.
+    pub(crate) fn replace_tempo(&mut self, tempo: Tempo) -> Tempo {
+        let old = std::mem::replace(&mut self.tempo, tempo);
+        self.follow_tempo();
+        old
+    }
+
+    // called before every block, so the tempo moves in steps of one block
+    pub(crate) fn follow_tempo(&mut self) {
+        let bar = self.position().bars;
+        let bpm = match &self.tempo {
+            Tempo::Fixed => return,
+            Tempo::Ramp {
+                to,
+                start_bar,
+                bars,
+                ..
+            } if bar >= start_bar + bars => {
+                let to = *to;
+                self.tempo = Tempo::Fixed;
+                to
+            }
+            Tempo::Ramp {
+                from,
+                to,
+                start_bar,
+                bars,
+            } => from + (to - from) * ((bar - start_bar) / bars).max(0.) as f32,
+            Tempo::Map(map) => map.bpm_at(bar),
+        };
+        if bpm != self.bpm {
+            self.change_bpm(bpm);
+        }
+    }
+
+    // the transport and the nodes on the song grid keep their beat, and so do the waiting
+    // updates and scheduled messages, which wait for a beat rather than a sample
+    pub(crate) fn change_bpm(&mut self, bpm: f32) {
+        self.bpm = bpm;
+        self.clock = self.samples_at(self.beats);
+        self.context
+            .send_msg_to_all(Message::SetTempo(bpm, self.clock));
+    }
+}
@@ -0,0 +1,72 @@
+use glicol_synth::Message;
+
+use crate::Engine;
+
+/// Whether the engine is running through the song.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TransportState {
+    #[default]
+    Playing,
+    /// Silent, keeping the position.
+    Paused,
+    /// Silent, back at the start.
+    Stopped,
+}
+
+/// Where the transport is in the song. A bar is 4 beats long.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransportPosition {
+    /// In samples at the current tempo, which move with it when the tempo changes.
+    pub samples: usize,
+    pub beats: f64,
+    pub bars: f64,
+}
+
+impl<const N: usize> Engine<N> {
+    pub fn play(&mut self) {
+        self.render_rest.clear();
+        self.transport = TransportState::Playing;
+    }
+
+    pub fn pause(&mut self) {
+        self.render_rest.clear();
+        self.transport = TransportState::Paused;
+    }
+
+    pub fn stop(&mut self) {
+        self.transport = TransportState::Stopped;
+        self.seek(0.);
+    }
+
+    /// Moves every time-based node to the start of `bar`, counted from 0.
+    pub fn seek(&mut self, bar: f32) {
+        self.render_rest.clear();
+        self.beats = bar.max(0.) as f64 * 4.;
+        self.clock = self.samples_at(self.beats);
+        self.context
+            .send_msg_to_all(Message::SetPosition(self.clock));
+    }
+
+    pub fn transport_state(&self) -> TransportState {
+        self.transport
+    }
+
+    pub fn position(&self) -> TransportPosition {
+        TransportPosition {
+            samples: self.clock,
+            beats: self.beats,
+            bars: self.beats / 4.,
+        }
+    }
+
+    // where `beats` is in samples, at the current tempo and sample rate
+    pub(crate) fn samples_at(&self, beats: f64) -> usize {
+        (beats * 60. / self.bpm as f64 * self.sr as f64).round() as usize
+    }
+
+    // how many beats `samples` last, at the current tempo and sample rate
+    pub(crate) fn beats_at(&self, samples: usize) -> f64 {
+        samples as f64 / self.sr as f64 * self.bpm as f64 / 60.
+    }
+}
@@ -1,7 +1,7 @@
 use glicol_synth::{
     compound::{Bd, Hh, SawSynth, Sn, SquSynth, TriSynth},
     delay::{DelayMs, DelayN},
-    effect::{Balance, Pan, Plate},
+    effect::{Balance, Out, Pan, Plate},
     envelope::{Adsr, EnvPerc},
     filter::{AllPassFilterGain, OnePole, ResonantHighPassFilter, ResonantLowPassFilter},
     operator::{Add, Mul},
@@ -9,11 +9,11 @@ use glicol_synth::{
     sequencer::{Arrange, Choose, Sequencer, Speed},
     signal::{ConstSig, Impulse, Noise, Points},
     synth::{MsgSynth, PatternSynth},
-    Node, Pass, Sum2,
+    Feedback, Message, Node, Pass, SampleBank, Sum2,
 };
 
 use glicol_parser::{
-    nodes::{self, Component, UsizeOrRef},
+    nodes::{self, Argument, Component, UsizeOrRef},
     ToInnerOwned as _,
 };
 
@@ -25,7 +25,7 @@ use glicol_synth::dynamic::Eval;
 #[cfg(feature = "use-samples")]
 use glicol_synth::sampling::{PSampler, Sampler};
 
-use crate::EngineError;
+use crate::{registry::NodeRegistry, EngineError};
 use glicol_synth::{BoxedNodeSend, NodeData}; //, Processor, Buffer, Input, Node
 use hashbrown::HashMap;
 
@@ -34,42 +34,29 @@ pub type GlicolNodeData<const N: usize> = NodeData<BoxedNodeSend<N>, N>;
 #[allow(unused_variables, unused_mut)]
 pub fn makenode<const N: usize>(
     component: &Component<'_>,
-    samples_dict: &HashMap<String, (&'static [f32], usize, usize)>,
+    samples: &SampleBank,
+    nodes: &NodeRegistry<N>,
     sr: usize,
     bpm: f32,
-    seed: usize,
+    seed: u64,
+    channels: usize,
 ) -> Result<(GlicolNodeData<N>, Vec<String>), EngineError> {
-    let (nodedata, reflist) = match component {
+    let (mut nodedata, reflist) = match component {
         #[cfg(feature = "use-samples")]
         Component::PSampler(psampler) => {
-            let mut samples_dict_selected = HashMap::new();
-            let (pattern, span) = match psampler {
-                nodes::PSampler::Event(_) => panic!("An event inside PSampler is not yet supported; please file an issue if you encounter this"),
-                nodes::PSampler::Pattern(ref pat) => (&pat.event, pat.span)
+            let pat = match psampler {
+                nodes::PSampler::Event(_) => {
+                    return Err(EngineError::UnsupportedNode(
+                        "An event inside psampler".to_string(),
+                    ))
+                }
+                nodes::PSampler::Pattern(ref pat) => pat,
             };
-
-            let pattern = pattern
-                .val_times
-                .iter()
-                .map(|(val, time)| {
-                    let value = match &val {
-                        nodes::EventValue::Number(_) => String::new(),
-                        nodes::EventValue::Symbol(sym) => sym.to_string(),
-                    };
-
-                    if !samples_dict.contains_key(&value) {
-                        return Err(EngineError::NonExistSample(value.clone()));
-                    } else {
-                        samples_dict_selected.insert(value.clone(), samples_dict[&value]);
-                    }
-
-                    Ok((value, *time))
-                })
-                .collect::<Result<Vec<_>, EngineError>>()?;
+            let span = pat.span;
+            let (pattern, selected) = psampler_pattern(pat, samples)?;
 
             (
-                PSampler::new(samples_dict_selected, sr, bpm, vec![], pattern, span)
-                    .to_boxed_nodedata(2),
+                PSampler::new(selected, sr, bpm, vec![], pattern, span).to_boxed_nodedata(2),
                 vec![],
             )
         }
@@ -99,25 +86,12 @@ pub fn makenode<const N: usize>(
                 .to_boxed_nodedata(1),
             vec![],
         ),
-        Component::PatternSynth(nodes::PatternSynth { symbol, span }) => {
-            let pattern = symbol.replace('`', "");
-            let events = pattern
-                .split(',')
-                .map(|event| {
-                    let mut result = event
-                        .split(' ')
-                        .filter(|x| !x.is_empty())
-                        .map(|x| x.replace(' ', "").parse::<f32>().unwrap());
-
-                    (result.next().unwrap(), result.next().unwrap())
-                })
-                .collect();
-
-            (
-                PatternSynth::new(events).sr(sr).to_boxed_nodedata(1),
-                vec![],
-            )
-        }
+        Component::PatternSynth(nodes::PatternSynth { symbol, span }) => (
+            PatternSynth::new(psynth_events(symbol)?)
+                .sr(sr)
+                .to_boxed_nodedata(1),
+            vec![],
+        ),
 
         #[cfg(feature = "bela")]
         Component::Adc(nodes::Adc { port }) => (
@@ -128,11 +102,14 @@ pub fn makenode<const N: usize>(
         #[cfg(feature = "use-samples")]
         Component::Sp(nodes::Sp { sample_sym }) => {
             let alt = "808bd";
-            let Some(sample) = samples_dict.get(*sample_sym) else {
+            let Some(sample) = samples.get(sample_sym) else {
                 return Err(EngineError::NonExistSample(sample_sym.to_string()));
             };
 
-            (Sampler::new(*sample, sr).to_boxed_nodedata(2), vec![])
+            (
+                Sampler::new(sample.clone(), sr).to_boxed_nodedata(2),
+                vec![],
+            )
         }
 
         #[cfg(feature = "use-meta")]
@@ -167,30 +144,20 @@ pub fn makenode<const N: usize>(
                         .sr(sr)
                         .to_boxed_nodedata(1)
                 }
-                nodes::Signal::Pattern(nodes::Pattern { event, span }) => {
-                    let pattern = event
-                        .val_times
-                        .iter()
-                        .map(|(val, time)| {
-                            let value = match val {
-                                nodes::EventValue::Number(num) => *num,
-                                nodes::EventValue::Symbol(_) => 100.0,
-                            };
-                            (value, *time)
-                        })
-                        .collect();
-
+                nodes::Signal::Pattern(pat) => {
                     // println!("pattern {:?}", pattern);
                     ResonantLowPassFilter::new()
                         .q(*qvalue)
-                        .pattern(pattern)
-                        .span(*span)
+                        .pattern(lpf_pattern(pat))
+                        .span(pat.span)
                         .bpm(bpm)
                         .sr(sr)
                         .to_boxed_nodedata(1)
                 }
                 nodes::Signal::Event(_) => {
-                    panic!("An event as a parameter to lpf is not currently supported")
+                    return Err(EngineError::UnsupportedNode(
+                        "An event as a parameter to lpf".to_string(),
+                    ))
                 }
             };
             (data, reflist)
@@ -207,6 +174,7 @@ pub fn makenode<const N: usize>(
                     nodes::NumberOrRef::Ref(_) => 100.0,
                 })
                 .q(*qvalue)
+                .sr(sr)
                 .to_boxed_nodedata(1);
 
             let mut reflist = vec![];
@@ -295,7 +263,9 @@ pub fn makenode<const N: usize>(
                 vec![s.to_string()],
             ),
         },
-        Component::Plate(nodes::Plate { mix }) => (Plate::new(*mix).to_boxed_nodedata(2), vec![]),
+        Component::Plate(nodes::Plate { mix }) => {
+            (Plate::new(*mix).sr(sr).to_boxed_nodedata(2), vec![])
+        }
         Component::Imp(nodes::Imp { param }) => match param {
             nodes::NumberOrRef::Number(v) => {
                 (Impulse::new().sr(sr).freq(*v).to_boxed_nodedata(1), vec![])
@@ -306,12 +276,18 @@ pub fn makenode<const N: usize>(
             ),
         },
         Component::Mul(nodes::Mul { param }) => match param {
-            nodes::NumberOrRef::Number(v) => (Mul::new(*v).to_boxed_nodedata(2), vec![]),
-            nodes::NumberOrRef::Ref(s) => (Mul::new(0.0).to_boxed_nodedata(2), vec![s.to_string()]),
+            nodes::NumberOrRef::Number(v) => (Mul::new(*v).sr(sr).to_boxed_nodedata(2), vec![]),
+            nodes::NumberOrRef::Ref(s) => (
+                Mul::new(0.0).sr(sr).to_boxed_nodedata(2),
+                vec![s.to_string()],
+            ),
         },
         Component::Pan(nodes::Pan { param }) => match param {
-            nodes::NumberOrRef::Number(v) => (Pan::new(*v).to_boxed_nodedata(2), vec![]),
-            nodes::NumberOrRef::Ref(s) => (Pan::new(0.0).to_boxed_nodedata(2), vec![s.to_string()]),
+            nodes::NumberOrRef::Number(v) => (Pan::new(*v).sr(sr).to_boxed_nodedata(2), vec![]),
+            nodes::NumberOrRef::Ref(s) => (
+                Pan::new(0.0).sr(sr).to_boxed_nodedata(2),
+                vec![s.to_string()],
+            ),
         },
         Component::Delayn(nodes::Delayn { param }) => match param {
             nodes::UsizeOrRef::Usize(v) => (DelayN::new(*v).to_boxed_nodedata(2), vec![]),
@@ -334,56 +310,78 @@ pub fn makenode<const N: usize>(
         Component::Onepole(nodes::Onepole { param }) => {
             get_one_para_from_number_or_ref::<N, OnePole>(param, 1)
         }
-        Component::Add(nodes::Add { param }) => get_one_para_from_number_or_ref::<N, Add>(param, 2),
+        Component::Add(nodes::Add { param }) => match param {
+            nodes::NumberOrRef::Number(v) => (Add::new(*v).sr(sr).to_boxed_nodedata(2), vec![]),
+            nodes::NumberOrRef::Ref(s) => (
+                Add::new(0.0).sr(sr).to_boxed_nodedata(2),
+                vec![s.to_string()],
+            ),
+        },
         Component::ConstSig(nodes::ConstSig { value }) => {
             (ConstSig::new(*value).sr(sr).to_boxed_nodedata(1), vec![])
         }
-        // todo: give sr to them
-        Component::Bd(nodes::Bd { param }) => get_one_para_from_number_or_ref::<N, Bd<N>>(param, 2),
-        Component::Hh(nodes::Hh { param }) => get_one_para_from_number_or_ref::<N, Hh<N>>(param, 2),
-        Component::Sn(nodes::Sn { param }) => get_one_para_from_number_or_ref::<N, Sn<N>>(param, 2),
-        Component::SawSynth(nodes::SawSynth { attack, decay }) => {
-            (SawSynth::new(*attack, *decay).to_boxed_nodedata(2), vec![])
-        }
-        Component::SquSynth(nodes::SquSynth { attack, decay }) => {
-            (SquSynth::new(*attack, *decay).to_boxed_nodedata(2), vec![])
-        }
-        Component::TriSynth(nodes::TriSynth { attack, decay }) => {
-            (TriSynth::new(*attack, *decay).to_boxed_nodedata(2), vec![])
-        }
+        Component::Bd(nodes::Bd { param }) => match param {
+            nodes::NumberOrRef::Number(v) => (Bd::<N>::new(*v).sr(sr).to_boxed_nodedata(2), vec![]),
+            nodes::NumberOrRef::Ref(s) => (
+                Bd::<N>::new(0.0).sr(sr).to_boxed_nodedata(2),
+                vec![s.to_string()],
+            ),
+        },
+        Component::Hh(nodes::Hh { param }) => match param {
+            nodes::NumberOrRef::Number(v) => (Hh::<N>::new(*v).sr(sr).to_boxed_nodedata(2), vec![]),
+            nodes::NumberOrRef::Ref(s) => (
+                Hh::<N>::new(0.0).sr(sr).to_boxed_nodedata(2),
+                vec![s.to_string()],
+            ),
+        },
+        Component::Sn(nodes::Sn { param }) => match param {
+            nodes::NumberOrRef::Number(v) => (Sn::<N>::new(*v).sr(sr).to_boxed_nodedata(2), vec![]),
+            nodes::NumberOrRef::Ref(s) => (
+                Sn::<N>::new(0.0).sr(sr).to_boxed_nodedata(2),
+                vec![s.to_string()],
+            ),
+        },
+        Component::SawSynth(nodes::SawSynth { attack, decay }) => (
+            SawSynth::new(*attack, *decay).sr(sr).to_boxed_nodedata(2),
+            vec![],
+        ),
+        Component::SquSynth(nodes::SquSynth { attack, decay }) => (
+            SquSynth::new(*attack, *decay).sr(sr).to_boxed_nodedata(2),
+            vec![],
+        ),
+        Component::TriSynth(nodes::TriSynth { attack, decay }) => (
+            TriSynth::new(*attack, *decay).sr(sr).to_boxed_nodedata(2),
+            vec![],
+        ),
         Component::Get(nodes::Get { reference }) => (
             NodeData::new2(BoxedNodeSend::new(Pass {})),
             vec![reference.to_string()],
         ),
-        Component::Seq(nodes::Seq { events }) => {
-            let mut reflist = Vec::<String>::new();
-            let mut order = HashMap::new();
-            let mut count = 0;
-            for event in events {
-                if let UsizeOrRef::Ref(s) = &event.1 {
-                    // reflist: ["~a", "~b", "~a"]
-                    if !reflist.iter().any(|r| r == s) {
-                        reflist.push(s.to_string());
-                        order.insert(s.to_string(), count);
-                        count += 1;
-                    }
-                }
+        // the engine writes the chain it reads into it before each block
+        Component::Fb(_) => (NodeData::new2(BoxedNodeSend::new(Feedback)), vec![]),
+        Component::Out(nodes::Out { channels: outs }) => {
+            if let Some(channel) = outs.iter().find(|c| **c == 0 || **c > channels) {
+                return Err(EngineError::InvalidParamValue(format!(
+                    "out {channel}: the output has channels 1 to {channels}"
+                )));
             }
-            (
-                Sequencer::new(events.to_inner_owned())
-                    .sr(sr)
-                    .bpm(bpm)
-                    .ref_order(order)
-                    .to_boxed_nodedata(2),
-                reflist,
-            )
+            let outs = outs.iter().map(|channel| channel - 1).collect();
+            (Out::new(outs).to_boxed_nodedata(channels), vec![])
         }
+        Component::Seq(nodes::Seq { events }) => (
+            Sequencer::new(events.to_inner_owned())
+                .sr(sr)
+                .bpm(bpm)
+                .ref_order(seq_ref_order(events))
+                .to_boxed_nodedata(2),
+            references(component),
+        ),
         Component::Choose(nodes::Choose { choices }) => (
-            Choose::new(choices.clone(), seed as u64).to_boxed_nodedata(2),
+            Choose::new(choices.clone(), seed).to_boxed_nodedata(2),
             vec![],
         ),
         Component::Mix(nodes::Mix { nodes }) => (
-            NodeData::new2(BoxedNodeSend::new(Sum2 {})),
+            NodeData::new2(BoxedNodeSend::new(Sum2::default())),
             nodes.iter().map(ToString::to_string).collect(),
         ),
         Component::Arrange(nodes::Arrange { events }) => {
@@ -403,17 +401,17 @@ pub fn makenode<const N: usize>(
                 reflist,
             )
         }
-        Component::Reverb(_) | Component::Expr(_) => {
-            panic!("{component:?} is currently not supported within the engine")
-        }
+        Component::Custom(custom) => nodes.build(custom, sr, bpm)?,
+        Component::Reverb(_) => return Err(EngineError::UnsupportedNode("reverb".to_string())),
+        Component::Expr(_) => return Err(EngineError::UnsupportedNode("expr".to_string())),
         #[cfg(not(feature = "use-samples"))]
         Component::Sp(_) | Component::PSampler(_) => {
-            panic!("The `use-samples` feature is required to use the `sp` or `psampler` node")
+            return Err(EngineError::MissingFeature("use-samples"))
         }
         #[cfg(not(feature = "bela"))]
-        Component::Adc(_) => panic!("The `bela` feature is required to use the `adc` node"),
+        Component::Adc(_) => return Err(EngineError::MissingFeature("bela")),
         #[cfg(not(feature = "use-meta"))]
-        Component::Meta(_) => panic!("The `use-meta` feature is required to use the `meta` node"),
+        Component::Meta(_) => return Err(EngineError::MissingFeature("use-meta")),
         // "sendpass" => {
         //     let reflist = match &paras[0] {
         //         GlicolPara::RefList(v) => {
@@ -424,9 +422,29 @@ pub fn makenode<const N: usize>(
         //     ( Pass{}.to_boxed_nodedata(2), reflist)
         // },
     };
+    // for the nodes with randomness, the others ignore it
+    nodedata.node.send_msg(Message::SetSeed(seed));
     Ok((nodedata, reflist))
 }
 
+/// The seed of the node at `position` in `chain`. It only depends on the engine seed and
+/// where the node is in the code, and neighbouring nodes get unrelated seeds.
+pub(crate) fn node_seed(seed: usize, chain: &str, position: usize) -> u64 {
+    // FNV-1a, as the std hashers may change between releases
+    let hash = chain
+        .bytes()
+        .chain((position as u64).to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash: u64, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+        });
+    // the splitmix64 finalizer
+    let mut z = hash ^ (seed as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    // the noise generators count up from their seed, so it stays far from overflowing
+    (z ^ (z >> 31)) >> 32
+}
+
 fn get_one_para_from_number_or_ref<const N: usize, T>(
     param: &nodes::NumberOrRef<&str>,
     channels: usize,
@@ -442,3 +460,334 @@ where
         ),
     }
 }
+
+// the pattern and the samples it plays
+fn psampler_pattern(
+    pat: &nodes::Pattern<'_>,
+    samples: &SampleBank,
+) -> Result<(Vec<(String, f32)>, SampleBank), EngineError> {
+    let mut selected = SampleBank::new();
+    let pattern = pat
+        .event
+        .val_times
+        .iter()
+        .map(|(val, time)| {
+            let value = match &val {
+                nodes::EventValue::Number(_) => String::new(),
+                nodes::EventValue::Symbol(sym) => sym.to_string(),
+            };
+
+            let Some(sample) = samples.get(&value) else {
+                return Err(EngineError::NonExistSample(value));
+            };
+            selected.insert(value.clone(), sample.clone());
+
+            Ok((value, *time))
+        })
+        .collect::<Result<Vec<_>, EngineError>>()?;
+    Ok((pattern, selected))
+}
+
+/// `data` as the sample `name`, if the samplers can play it: one or two channels of the
+/// same, non-zero length.
+#[cfg(feature = "use-samples")]
+pub(crate) fn checked_sample(
+    name: &str,
+    data: std::sync::Arc<[f32]>,
+    channels: usize,
+    sr: usize,
+) -> Result<glicol_synth::Sample, EngineError> {
+    if !(1..=2).contains(&channels) || data.is_empty() || !data.len().is_multiple_of(channels) {
+        return Err(EngineError::InvalidSample(format!(
+            "{name}: {} values in {channels} channels",
+            data.len()
+        )));
+    }
+    Ok(glicol_synth::Sample::new(data, channels, sr))
+}
+
+/// The messages that give every node playing the sample `name` its current data.
+#[cfg(feature = "use-samples")]
+pub(crate) fn sample_messages(
+    ast: &nodes::Ast<'_>,
+    name: &str,
+    samples: &SampleBank,
+) -> Vec<(String, usize, Message)> {
+    let plays = |component: &Component<'_>| match component {
+        Component::Sp(sp) => sp.sample_sym == name,
+        Component::PSampler(nodes::PSampler::Pattern(pat)) => pat
+            .event
+            .val_times
+            .iter()
+            .any(|(val, _)| *val == nodes::EventValue::Symbol(name)),
+        _ => false,
+    };
+    let mut messages = vec![];
+    for (chain_name, chain) in &ast.nodes {
+        for (i, component) in chain.iter().enumerate().filter(|(_, c)| plays(c)) {
+            let updates = update_messages(component, component, samples).unwrap_or_default();
+            messages.extend(
+                updates
+                    .into_iter()
+                    .map(|msg| (chain_name.to_string(), i, msg)),
+            );
+        }
+    }
+    messages
+}
+
+// "time midi, time midi, ..."
+fn psynth_events(symbol: &str) -> Result<Vec<(f32, f32)>, EngineError> {
+    symbol
+        .replace('`', "")
+        .split(',')
+        .map(|event| {
+            let mut numbers = event.split_whitespace().map(str::parse::<f32>);
+            match (numbers.next(), numbers.next(), numbers.next()) {
+                (Some(Ok(time)), Some(Ok(midi)), None) => Ok((time, midi)),
+                _ => Err(EngineError::InvalidPattern(symbol.to_string())),
+            }
+        })
+        .collect()
+}
+
+fn lpf_pattern(pat: &nodes::Pattern<'_>) -> Vec<(f32, f32)> {
+    pat.event
+        .val_times
+        .iter()
+        .map(|(val, time)| {
+            let value = match val {
+                nodes::EventValue::Number(num) => *num,
+                nodes::EventValue::Symbol(_) => 100.0,
+            };
+            (value, *time)
+        })
+        .collect()
+}
+
+// where each distinct ref of a sequencer sits among its inputs
+fn seq_ref_order(events: &[(f32, UsizeOrRef<&str>)]) -> HashMap<String, usize> {
+    let mut order = HashMap::new();
+    for event in events {
+        if let UsizeOrRef::Ref(s) = &event.1 {
+            // refs: ["~a", "~b", "~a"]
+            if !order.contains_key(*s) {
+                order.insert(s.to_string(), order.len());
+            }
+        }
+    }
+    order
+}
+
+/// The messages that turn the node made from `old` into one made from `new`, keeping its
+/// state. `None` if the node has to be rebuilt: the components are of different types, a
+/// parameter switched between a number and a ref, or the node can't change it in place.
+pub(crate) fn update_messages(
+    old: &Component<'_>,
+    new: &Component<'_>,
+    samples: &SampleBank,
+) -> Option<Vec<Message>> {
+    use nodes::NumberOrRef::{Number, Ref};
+
+    // only the numbers that changed are sent
+    let numbers = |changes: &[(u8, f32, f32)]| {
+        changes
+            .iter()
+            .filter(|(_, old, new)| old != new)
+            .map(|(pos, _, new)| Message::SetToNumber(*pos, *new))
+            .collect::<Vec<_>>()
+    };
+    let number_or_ref =
+        |pos: u8, old: &nodes::NumberOrRef<&str>, new: &nodes::NumberOrRef<&str>| {
+            match (old, new) {
+                (Number(old), Number(new)) => Some(numbers(&[(pos, *old, *new)])),
+                // the refs are rewired along with the rest of the graph
+                (Ref(_), Ref(_)) => Some(vec![]),
+                _ => None,
+            }
+        };
+
+    let messages = match (old, new) {
+        (Component::Sin(old), Component::Sin(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Saw(old), Component::Saw(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Squ(old), Component::Squ(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Tri(old), Component::Tri(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Imp(old), Component::Imp(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Mul(old), Component::Mul(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Add(old), Component::Add(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Pan(old), Component::Pan(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Onepole(old), Component::Onepole(new)) => {
+            number_or_ref(0, &old.param, &new.param)?
+        }
+        (Component::Delayms(old), Component::Delayms(new)) => {
+            number_or_ref(0, &old.param, &new.param)?
+        }
+        (Component::Bd(old), Component::Bd(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Sn(old), Component::Sn(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Hh(old), Component::Hh(new)) => number_or_ref(0, &old.param, &new.param)?,
+        (Component::Delayn(old), Component::Delayn(new)) => match (&old.param, &new.param) {
+            (UsizeOrRef::Usize(old), UsizeOrRef::Usize(new)) => {
+                numbers(&[(0, *old as f32, *new as f32)])
+            }
+            (UsizeOrRef::Ref(_), UsizeOrRef::Ref(_)) => vec![],
+            _ => return None,
+        },
+        (Component::ConstSig(old), Component::ConstSig(new)) => {
+            numbers(&[(0, old.value, new.value)])
+        }
+        (Component::Speed(old), Component::Speed(new)) => numbers(&[(0, old.speed, new.speed)]),
+        (Component::Plate(old), Component::Plate(new)) => numbers(&[(0, old.mix, new.mix)]),
+        (Component::Choose(_), Component::Choose(new)) => {
+            vec![Message::SetToNumberList(0, new.choices.clone())]
+        }
+        (Component::EnvPerc(old), Component::EnvPerc(new)) => {
+            numbers(&[(0, old.attack, new.attack), (1, old.decay, new.decay)])
+        }
+        (Component::Adsr(old), Component::Adsr(new)) => numbers(&[
+            (0, old.attack, new.attack),
+            (1, old.decay, new.decay),
+            (2, old.sustain, new.sustain),
+            (3, old.release, new.release),
+        ]),
+        (Component::SawSynth(old), Component::SawSynth(new)) => {
+            numbers(&[(0, old.attack, new.attack), (1, old.decay, new.decay)])
+        }
+        (Component::SquSynth(old), Component::SquSynth(new)) => {
+            numbers(&[(0, old.attack, new.attack), (1, old.decay, new.decay)])
+        }
+        (Component::TriSynth(old), Component::TriSynth(new)) => {
+            numbers(&[(0, old.attack, new.attack), (1, old.decay, new.decay)])
+        }
+        (Component::MsgSynth(old), Component::MsgSynth(new)) if old.symbol == new.symbol => {
+            numbers(&[(1, old.attack, new.attack), (2, old.decay, new.decay)])
+        }
+        (Component::PatternSynth(old), Component::PatternSynth(new)) if old.span == new.span => {
+            psynth_events(new.symbol).ok()?;
+            vec![Message::SetToSymbol(0, new.symbol.to_string())]
+        }
+        (Component::Rhpf(old), Component::Rhpf(new)) => {
+            let mut messages = number_or_ref(0, &old.cutoff, &new.cutoff)?;
+            messages.extend(numbers(&[(1, old.qvalue, new.qvalue)]));
+            messages
+        }
+        (Component::ApfmsGain(old), Component::ApfmsGain(new)) => {
+            let mut messages = number_or_ref(0, &old.delay, &new.delay)?;
+            messages.extend(numbers(&[(1, old.gain, new.gain)]));
+            messages
+        }
+        (Component::Lpf(old), Component::Lpf(new)) => {
+            let mut messages = match (&old.signal, &new.signal) {
+                (nodes::Signal::Number(old), nodes::Signal::Number(new)) => {
+                    numbers(&[(0, *old, *new)])
+                }
+                (nodes::Signal::Reference(_), nodes::Signal::Reference(_)) => vec![],
+                (nodes::Signal::Pattern(_), nodes::Signal::Pattern(new)) => {
+                    vec![Message::SetPattern(lpf_pattern(new), new.span)]
+                }
+                _ => return None,
+            };
+            messages.extend(numbers(&[(1, old.qvalue, new.qvalue)]));
+            messages
+        }
+        (Component::Seq(_), Component::Seq(new)) => vec![
+            Message::SetToSeq(0, new.events.to_inner_owned()),
+            Message::SetRefOrder(seq_ref_order(&new.events)),
+        ],
+        (Component::Sp(_), Component::Sp(new)) => {
+            vec![Message::SetToSamples(
+                0,
+                samples.get(new.sample_sym)?.clone(),
+            )]
+        }
+        (
+            Component::PSampler(nodes::PSampler::Pattern(_)),
+            Component::PSampler(nodes::PSampler::Pattern(new)),
+        ) => {
+            let (pattern, selected) = psampler_pattern(new, samples).ok()?;
+            vec![Message::SetSamplePattern(pattern, new.span, selected)]
+        }
+        (Component::Eval(_), Component::Eval(new)) => {
+            vec![Message::SetToSymbol(0, new.code.code.to_string())]
+        }
+        (Component::Meta(_), Component::Meta(new)) => {
+            vec![Message::SetToSymbol(0, new.code.code.to_string())]
+        }
+        (Component::Custom(old), Component::Custom(new))
+            if old.name == new.name && old.args.len() == new.args.len() =>
+        {
+            let mut messages = vec![];
+            for (pos, (old, new)) in (0..).zip(old.args.iter().zip(&new.args)) {
+                match (old, new) {
+                    (Argument::Number(old), Argument::Number(new)) => {
+                        messages.extend(numbers(&[(pos, *old, *new)]))
+                    }
+                    (Argument::Symbol(old), Argument::Symbol(new)) if old != new => {
+                        messages.push(Message::SetToSymbol(pos, new.to_string()))
+                    }
+                    (Argument::Symbol(_), Argument::Symbol(_))
+                    | (Argument::Ref(_), Argument::Ref(_)) => {}
+                    _ => return None,
+                }
+            }
+            messages
+        }
+        // nodes that only read other chains
+        (Component::Mix(_), Component::Mix(_))
+        | (Component::Balance(_), Component::Balance(_))
+        | (Component::Get(_), Component::Get(_))
+        | (Component::Fb(_), Component::Fb(_)) => vec![],
+        _ => return None,
+    };
+    Some(messages)
+}
+
+/// The chains a component reads from, in the order `makenode` connects them.
+pub(crate) fn references(component: &Component<'_>) -> Vec<String> {
+    let mut reflist: Vec<String> = vec![];
+    if let Component::Fb(_) = component {
+        return reflist;
+    }
+    for r in component.all_references() {
+        // a sequencer only needs one connection per ref, its ref order takes care of the rest
+        if matches!(component, Component::Seq(_)) && reflist.iter().any(|s| s == r) {
+            continue;
+        }
+        reflist.push(r.to_string());
+    }
+    reflist
+}
+
+/// The chain an `fb` node plays one block late. It is read without a connection, so it
+/// can close a loop.
+pub(crate) fn feedback_reference<'a>(component: &Component<'a>) -> Option<&'a str> {
+    match component {
+        Component::Fb(nodes::Fb { reference }) => Some(reference),
+        _ => None,
+    }
+}
+
+/// Parses `"chain,position,param,value;"` commands into messages for single nodes.
+/// A value that is not a number is sent as a symbol.
+pub(crate) fn parse_msg(msg: &str) -> Vec<(String, usize, Message)> {
+    let commands: String = msg.chars().filter(|c| !c.is_whitespace()).collect::<_>();
+    commands
+        .split(';')
+        .filter(|c| !c.is_empty())
+        .filter_map(|command| {
+            let mut list = command.split(',');
+            let (Some(chain_name), Some(chain_pos), Some(param_pos), Some(value)) =
+                (list.next(), list.next(), list.next(), list.next())
+            else {
+                return None; // todo: this should be an error
+            };
+
+            let chain_pos = chain_pos.parse::<usize>().unwrap_or_default();
+            let param_pos = param_pos.parse::<u8>().unwrap_or_default();
+            let message = match value.parse::<f32>() {
+                Ok(v) => Message::SetToNumber(param_pos, v),
+                Err(_) => Message::SetToSymbol(param_pos, value.to_string()),
+            };
+            Some((chain_name.to_string(), chain_pos, message))
+        })
+        .collect()
+}
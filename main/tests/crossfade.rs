@@ -0,0 +1,55 @@
+use glicol::*;
+
+#[test]
+fn replaced_node_fades_out() {
+    let mut engine = Engine::<128>::new();
+    engine.set_update_crossfade_ms(10.); // 441 samples
+    engine.update_with_code("o: constsig 1").unwrap();
+    engine.render(0.1);
+
+    engine.update_with_code("o: constsig 0.5").unwrap();
+    let samples: Vec<f32> = engine.render(0.02).chunks(2).map(|f| f[0]).collect();
+    assert!((samples[0] - 1.).abs() < 1e-6);
+    assert!(samples[200] < 1. && samples[200] > 0.5);
+    assert!(samples.windows(2).all(|w| w[1] <= w[0]));
+    assert_eq!(samples[500], 0.5);
+}
+
+#[test]
+fn kept_nodes_stay_in_place_during_fade() {
+    let mut engine = Engine::<128>::new();
+    engine.set_update_crossfade_ms(10.);
+    engine.update_with_code("o: constsig 1 >> mul 0.5").unwrap();
+    engine.render(0.1);
+
+    // the new `add` fades in between the kept nodes
+    engine
+        .update_with_code("o: constsig 1 >> add 1 >> mul 0.5")
+        .unwrap();
+    let samples: Vec<f32> = engine.render(0.02).chunks(2).map(|f| f[0]).collect();
+    assert!((samples[0] - 0.5).abs() < 1e-6);
+    assert!(samples[200] > 0.5 && samples[200] < 1.);
+    assert_eq!(samples[500], 1.);
+
+    // the faded out nodes are dropped with the next update
+    let count = engine.context.graph.node_count();
+    engine
+        .update_with_code("o: constsig 1 >> add 1 >> mul 0.5")
+        .unwrap();
+    assert_eq!(engine.context.graph.node_count(), count - 1);
+}
+
+#[test]
+fn removed_chain_fades_out() {
+    let mut engine = Engine::<128>::new();
+    engine.set_update_crossfade_ms(10.);
+    engine
+        .update_with_code("a: constsig 1\nb: constsig 0")
+        .unwrap();
+    engine.render(0.1);
+
+    engine.update_with_code("b: constsig 0").unwrap();
+    let samples: Vec<f32> = engine.render(0.02).chunks(2).map(|f| f[0]).collect();
+    assert!((samples[0] - 1.).abs() < 1e-6);
+    assert_eq!(samples[500], 0.);
+}
@@ -0,0 +1,110 @@
+use glicol::*;
+use glicol_synth::{Buffer, Input, Message, Node, ParamInfo};
+use hashbrown::HashMap;
+
+// a constant plus whatever chain drives it
+struct Level {
+    value: f32,
+}
+
+impl Level {
+    const PARAMS: &[ParamInfo] = &[ParamInfo::number("value", 0, 0.).with_ref()];
+}
+
+impl<const N: usize> Node<N> for Level {
+    fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
+        for (i, out) in output[0].iter_mut().enumerate() {
+            *out = self.value
+                + inputs
+                    .values()
+                    .map(|input| input.buffers()[0][i])
+                    .sum::<f32>();
+        }
+    }
+
+    fn send_msg(&mut self, info: Message) {
+        if let Message::SetToNumber(0, value) = info {
+            self.value = value;
+        }
+    }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
+}
+
+fn engine() -> Engine<128> {
+    let mut engine = Engine::<128>::new();
+    engine.register_node("level", Level::PARAMS, |args, _sr, _bpm| {
+        let value = match args[0] {
+            NodeArg::Number(value) => value,
+            _ => 0.,
+        };
+        Level { value }.to_boxed_nodedata(1)
+    });
+    engine
+}
+
+#[test]
+fn registered_nodes_play_and_update() {
+    let mut engine = engine();
+    engine.update_with_code("o: level 0.25").unwrap();
+    assert_eq!(engine.next_block(&[])[0][0], 0.25);
+
+    engine.update_with_code("o: level 0.5").unwrap();
+    assert_eq!(engine.next_block(&[])[0][0], 0.5);
+
+    engine
+        .set_param(
+            "o",
+            NodeSelector::Type("level"),
+            ParamId::Name("value"),
+            0.75.into(),
+        )
+        .unwrap();
+    assert_eq!(engine.next_block(&[])[0][0], 0.75);
+    let node = &engine.describe().chains[0].nodes[0];
+    assert_eq!(node.name, "level");
+    assert_eq!(
+        node.params[0].value,
+        ParamSetting::Value(ParamValue::Number(0.75))
+    );
+}
+
+#[test]
+fn references_are_inputs() {
+    let (mut handle, mut processor) = engine().split(8);
+    handle
+        .update_with_code("~mod: sig 0.5\no: level ~mod >> mul 0.5")
+        .unwrap();
+    assert_eq!(processor.next_block(&[])[0][0], 0.25);
+    assert_eq!(handle.describe().chains[0].nodes[0].references, ["~mod"]);
+}
+
+#[test]
+fn handles_register_nodes() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(8);
+    assert!(handle.update_with_code("o: level 0.25").is_err());
+    handle.register_node("level", Level::PARAMS, |_, _, _| {
+        Level { value: 0.25 }.to_boxed_nodedata(1)
+    });
+    handle.update_with_code("o: level 0.25").unwrap();
+    assert_eq!(processor.next_block(&[])[0][0], 0.25);
+}
+
+#[test]
+fn checks_the_arguments() {
+    let mut engine = engine();
+    assert_eq!(
+        engine.update_with_code("o: rumble 40"),
+        Err(EngineError::NonExistNode("rumble".to_string()))
+    );
+    assert!(matches!(
+        engine.update_with_code("o: level 1 2"),
+        Err(EngineError::InvalidParamValue(_))
+    ));
+    assert!(matches!(
+        engine.update_with_code(r"o: level \low"),
+        Err(EngineError::InvalidParamValue(_))
+    ));
+}
@@ -0,0 +1,115 @@
+use glicol::*;
+
+const CODE: &str = "~mod: sin 2 >> mul 100 >> add 300
+o: sin ~mod >> mul 0.5";
+
+fn node(chain: &str, position: usize) -> NodeId {
+    NodeId {
+        chain: chain.to_string(),
+        position,
+    }
+}
+
+fn value(description: &GraphDescription, chain: &str, position: usize) -> ParamSetting {
+    let chain = description.chains.iter().find(|c| c.name == chain).unwrap();
+    chain.nodes[position].params[0].value.clone()
+}
+
+#[test]
+fn describes_chains_params_and_edges() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(CODE).unwrap();
+    let description = engine.describe();
+
+    let names: Vec<_> = description.chains.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["o", "~input", "~mod"]);
+    let o = &description.chains[0];
+    assert_eq!(o.nodes[0].name, "sin");
+    assert_eq!(o.nodes[0].references, ["~mod"]);
+    assert_eq!(
+        o.nodes[0].params,
+        [ParamDescription {
+            name: "freq".to_string(),
+            index: 0,
+            value: ParamSetting::Reference("~mod".to_string()),
+        }]
+    );
+    assert_eq!(
+        value(&description, "~mod", 2),
+        ParamSetting::Value(ParamValue::Number(300.))
+    );
+
+    let edge = |from, to, kind| EdgeDescription { from, to, kind };
+    for expected in [
+        edge(node("~mod", 0), Some(node("~mod", 1)), EdgeKind::Chain),
+        edge(node("~mod", 2), Some(node("o", 0)), EdgeKind::Reference),
+        edge(node("o", 0), Some(node("o", 1)), EdgeKind::Chain),
+        edge(node("o", 1), None, EdgeKind::Output),
+    ] {
+        assert!(description.edges.contains(&expected), "{expected:?}");
+    }
+    assert_eq!(description.edges.len(), 5);
+}
+
+#[test]
+fn shows_values_set_since_the_code() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(CODE).unwrap();
+    engine
+        .set_param(
+            "o",
+            NodeSelector::Type("mul"),
+            ParamId::Name("value"),
+            0.25.into(),
+        )
+        .unwrap();
+    let set = ParamSetting::Value(ParamValue::Number(0.25));
+    assert_eq!(value(&engine.describe(), "o", 1), set);
+
+    // the node moved, the value with it
+    engine
+        .update_with_code("~mod: sin 2 >> mul 100 >> add 300\no: sin ~mod >> lpf 800 1 >> mul 0.5")
+        .unwrap();
+    assert_eq!(value(&engine.describe(), "o", 2), set);
+
+    // the code sets it again
+    engine
+        .update_with_code("~mod: sin 2 >> mul 100 >> add 300\no: sin ~mod >> lpf 800 1 >> mul 0.3")
+        .unwrap();
+    assert_eq!(
+        value(&engine.describe(), "o", 2),
+        ParamSetting::Value(ParamValue::Number(0.3))
+    );
+}
+
+#[test]
+fn handle_describes_what_it_sent() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(CODE).unwrap();
+    engine.send_msg("~mod, 1, 0, 50");
+    let expected = engine.describe();
+
+    let (mut handle, _processor) = Engine::<128>::new().split(8);
+    handle.update_with_code(CODE).unwrap();
+    handle.send_msg("~mod, 1, 0, 50").unwrap();
+    assert_eq!(handle.describe(), expected);
+    assert_eq!(
+        value(&expected, "~mod", 1),
+        ParamSetting::Value(ParamValue::Number(50.))
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn round_trips_through_json() {
+    let mut engine = Engine::<128>::new();
+    engine
+        .update_with_code(r#"o: choose 60 62 >> lpf "300@0.0 600@0.5"(1) 1.0"#)
+        .unwrap();
+    let description = engine.describe();
+    let json = serde_json::to_string(&description).unwrap();
+    assert_eq!(
+        serde_json::from_str::<GraphDescription>(&json).unwrap(),
+        description
+    );
+}
@@ -0,0 +1,67 @@
+use glicol::*;
+
+#[test]
+fn changed_parameter_keeps_the_node() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: sin 440 >> mul 0.5").unwrap();
+    let before = engine.index_info["o"].clone();
+
+    engine.update_with_code("o: sin 440 >> mul 0.25").unwrap();
+    assert_eq!(engine.index_info["o"], before);
+}
+
+#[test]
+fn changed_parameter_keeps_the_phase() {
+    let mut updated = Engine::<128>::new();
+    updated
+        .update_with_code("o: sin 440 >> lpf 1000 1.0")
+        .unwrap();
+    let mut sent = Engine::<128>::new();
+    sent.update_with_code("o: sin 440 >> lpf 1000 1.0").unwrap();
+    for _ in 0..10 {
+        assert_eq!(updated.next_block(&[]), sent.next_block(&[]));
+    }
+
+    updated
+        .update_with_code("o: sin 660 >> lpf 1200 0.8")
+        .unwrap();
+    sent.send_msg("o,0,0,660; o,1,0,1200; o,1,1,0.8");
+    for _ in 0..10 {
+        assert_eq!(updated.next_block(&[]), sent.next_block(&[]));
+    }
+}
+
+#[test]
+fn inserted_node_keeps_its_neighbours() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: sin 440 >> mul 0.5").unwrap();
+    let before = engine.index_info["o"].clone();
+
+    engine
+        .update_with_code("o: sin 220 >> lpf 1000 1.0 >> mul 0.3")
+        .unwrap();
+    let after = &engine.index_info["o"];
+    assert_eq!((after[0], after[2]), (before[0], before[1]));
+}
+
+#[test]
+fn number_replaced_by_ref() {
+    let mut engine = Engine::<128>::new();
+    let mut twin = Engine::<128>::new();
+    engine.update_with_code("o: sin 440 >> mul 0.25").unwrap();
+    twin.update_with_code("o: sin 440 >> mul 0.25").unwrap();
+    let before = engine.index_info["o"].clone();
+    for _ in 0..4 {
+        assert_eq!(engine.next_block(&[]), twin.next_block(&[]));
+    }
+
+    // the oscillator keeps running, the mul now follows the ref
+    engine
+        .update_with_code("o: sin 440 >> mul ~amp\n~amp: constsig 0.5")
+        .unwrap();
+    twin.update_with_code("o: sin 440 >> mul 0.5").unwrap();
+    assert_eq!(engine.index_info["o"][0], before[0]);
+    for _ in 0..4 {
+        assert_eq!(engine.next_block(&[]), twin.next_block(&[]));
+    }
+}
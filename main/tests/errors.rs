@@ -0,0 +1,137 @@
+use glicol::*;
+
+const RUNNING: &str = "o: sin 440 >> mul ~amp\n~amp: sin 1 >> mul 0.3 >> add 0.5";
+
+// a failed update must leave the graph and the sound exactly as they were
+fn assert_rejected(code: &str, is_expected: impl Fn(&EngineError) -> bool) {
+    let mut engine = Engine::<128>::new();
+    let mut untouched = Engine::<128>::new();
+    engine.update_with_code(RUNNING).unwrap();
+    untouched.update_with_code(RUNNING).unwrap();
+    for _ in 0..4 {
+        assert_eq!(engine.next_block(&[]), untouched.next_block(&[]));
+    }
+
+    let index_info = engine.index_info.clone();
+    let node_count = engine.context.graph.node_count();
+    let err = engine.update_with_code(code).unwrap_err();
+    assert!(is_expected(&err), "unexpected error for {code:?}: {err:?}");
+
+    assert_eq!(engine.index_info, index_info);
+    assert_eq!(engine.context.graph.node_count(), node_count);
+    for _ in 0..8 {
+        assert_eq!(engine.next_block(&[]), untouched.next_block(&[]));
+    }
+}
+
+#[test]
+fn parsing_error() {
+    assert_rejected("o: sin 440 >>", |err| {
+        matches!(err, EngineError::ParsingError(_))
+    });
+}
+
+#[test]
+fn missing_reference() {
+    assert_rejected("o: sin 880 >> mul ~nothing", |err| {
+        *err == EngineError::NonExistReference("~nothing".to_string())
+    });
+}
+
+#[test]
+fn later_chain_fails() {
+    // the first chain is fine and changes a parameter in place, but nothing may be sent
+    assert_rejected(
+        "o: sin 220 >> mul ~amp\n~amp: sin 1 >> mul 0.1 >> add ~missing",
+        |err| *err == EngineError::NonExistReference("~missing".to_string()),
+    );
+}
+
+#[test]
+fn cycle() {
+    assert_rejected("o: ~a\n~a: sin ~b\n~b: ~a >> mul 2", |err| {
+        *err == EngineError::Cycle(vec!["~a".to_string(), "~b".to_string()])
+    });
+    assert_rejected("o: sin 440 >> mul o", |err| {
+        *err == EngineError::Cycle(vec!["o".to_string()])
+    });
+}
+
+#[test]
+fn unsupported_nodes() {
+    assert_rejected("o: expr `x`", |err| {
+        *err == EngineError::UnsupportedNode("expr".to_string())
+    });
+    assert_rejected("o: sin 440 >> reverb 0.1 0.2 0.3 0.4 0.5", |err| {
+        *err == EngineError::UnsupportedNode("reverb".to_string())
+    });
+    assert_rejected(r#"o: saw 400 >> lpf "100@0.0 200@0.5" 1.0"#, |err| {
+        matches!(err, EngineError::UnsupportedNode(_))
+    });
+}
+
+#[test]
+fn invalid_pattern() {
+    assert_rejected("o: psynth `0 60, later` 1", |err| {
+        matches!(err, EngineError::InvalidPattern(_))
+    });
+
+    // also when the node would have been updated in place
+    let mut engine = Engine::<128>::new();
+    engine
+        .update_with_code("o: psynth `0 60, 0.5 62` 1")
+        .unwrap();
+    assert!(matches!(
+        engine.update_with_code("o: psynth `0 60, later` 1"),
+        Err(EngineError::InvalidPattern(_))
+    ));
+}
+
+#[cfg(not(feature = "bela"))]
+#[test]
+fn missing_bela_feature() {
+    assert_rejected("o: adc 1", |err| {
+        *err == EngineError::MissingFeature("bela")
+    });
+}
+
+#[cfg(not(feature = "use-samples"))]
+#[test]
+fn missing_samples_feature() {
+    assert_rejected(r"o: seq 60 >> sp \808", |err| {
+        *err == EngineError::MissingFeature("use-samples")
+    });
+}
+
+#[cfg(not(feature = "use-meta"))]
+#[test]
+fn missing_meta_feature() {
+    for code in ["o: meta `output`", "o: script `output`"] {
+        assert_rejected(code, |err| *err == EngineError::MissingFeature("use-meta"));
+    }
+}
+
+#[cfg(feature = "use-samples")]
+#[test]
+fn missing_sample() {
+    assert_rejected(r"o: seq 60 >> sp \808", |err| {
+        *err == EngineError::NonExistSample(r"\808".to_string())
+    });
+    assert_rejected(r#"o: psampler "\808@0.0"(1)"#, |err| {
+        *err == EngineError::NonExistSample(r"\808".to_string())
+    });
+}
+
+#[test]
+fn full_queue() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(1);
+    handle.update_with_code("o: sin 440").unwrap();
+    assert_eq!(
+        handle.update_with_code("o: sin 220"),
+        Err(EngineError::QueueFull)
+    );
+    // the rejected code is not what the next update is diffed against
+    processor.next_block(&[]);
+    handle.update_with_code("o: sin 440 >> mul 0.5").unwrap();
+    assert_eq!(handle.node_count(), 2);
+}
@@ -0,0 +1,59 @@
+use glicol::*;
+
+#[test]
+fn reads_one_block_late() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: fb ~a\n~a: sig 0.5").unwrap();
+    assert_eq!(engine.next_block(&[])[0][0], 0.);
+    assert_eq!(engine.next_block(&[])[0][0], 0.5);
+    assert_eq!(engine.next_block(&[])[1][127], 0.5);
+}
+
+#[test]
+fn closes_a_loop() {
+    let mut engine = Engine::<128>::new();
+    engine
+        .update_with_code("o: ~a\n~a: sig 0.5 >> add ~b\n~b: fb ~a >> mul 0.5")
+        .unwrap();
+    let blocks: Vec<f32> = (0..4).map(|_| engine.next_block(&[])[0][0]).collect();
+    assert_eq!(blocks, [0.5, 0.75, 0.875, 0.9375]);
+
+    // the loop keeps running through updates that keep the `fb` node
+    engine
+        .update_with_code("o: ~a\n~a: sig 0.5 >> add ~b\n~b: fb ~a >> mul 0.25")
+        .unwrap();
+    assert_eq!(engine.next_block(&[])[0][0], 0.5 + 0.9375 * 0.25);
+}
+
+#[test]
+fn describes_feedback_edges() {
+    let (mut handle, _processor) = Engine::<128>::new().split(8);
+    handle
+        .update_with_code("o: sin ~mod\n~mod: fb o >> mul 100 >> add 200")
+        .unwrap();
+    let description = handle.describe();
+    let node = |chain: &str, position| NodeId {
+        chain: chain.to_string(),
+        position,
+    };
+    assert!(description.edges.contains(&EdgeDescription {
+        from: node("o", 0),
+        to: Some(node("~mod", 0)),
+        kind: EdgeKind::Feedback,
+    }));
+    assert!(description.edges.contains(&EdgeDescription {
+        from: node("~mod", 2),
+        to: Some(node("o", 0)),
+        kind: EdgeKind::Reference,
+    }));
+}
+
+#[test]
+fn reset_keeps_the_input() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: fb ~a\n~a: sig 0.5").unwrap();
+    engine.reset();
+    engine.update_with_code("o: ~input >> mul 0.5").unwrap();
+    let input = [1.; 128];
+    assert_eq!(engine.next_block(&[&input, &input])[0][0], 0.5);
+}
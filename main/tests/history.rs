@@ -0,0 +1,96 @@
+use glicol::*;
+
+fn first_sample(engine: &mut Engine<128>) -> f32 {
+    engine.next_block(&[])[0][0]
+}
+
+#[test]
+fn steps_back_and_forth() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 0.1").unwrap();
+    engine.update_with_code("o: constsig 0.2").unwrap();
+    engine.update_with_code("o: constsig 0.3").unwrap();
+    assert_eq!(engine.history().current(), Some(2));
+
+    assert_eq!(engine.undo(), Ok(Some(1)));
+    assert_eq!(first_sample(&mut engine), 0.2);
+    assert_eq!(engine.undo(), Ok(Some(0)));
+    assert_eq!(engine.undo(), Ok(None));
+    assert_eq!(first_sample(&mut engine), 0.1);
+    assert_eq!(engine.redo(), Ok(Some(1)));
+    assert_eq!(first_sample(&mut engine), 0.2);
+
+    // new code drops what was undone
+    engine.update_with_code("o: constsig 0.4").unwrap();
+    assert_eq!(engine.redo(), Ok(None));
+    let versions: Vec<usize> = engine.history().iter().map(|(v, _)| v).collect();
+    assert_eq!(versions, [0, 1, 3]);
+}
+
+#[test]
+fn reverts_to_a_version() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 0.1").unwrap();
+    engine.update_with_code("o: constsig 0.2 >> mul 2").unwrap();
+    engine.update_with_code("o: constsig 0.3").unwrap();
+    engine.revert_to(1).unwrap();
+    assert_eq!(first_sample(&mut engine), 0.4);
+    assert_eq!(engine.history().get(1), Some("o: constsig 0.2 >> mul 2"));
+    assert_eq!(engine.redo(), Ok(Some(2)));
+    assert_eq!(engine.revert_to(5), Err(EngineError::NonExistVersion(5)));
+}
+
+#[test]
+fn keeps_only_good_code() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 0.1").unwrap();
+    assert!(engine.update_with_code("o: constsig").is_err());
+    engine.update_with_code("o: constsig 0.1").unwrap();
+    assert_eq!(engine.history().iter().count(), 1);
+}
+
+#[test]
+fn is_bounded() {
+    let mut engine = Engine::<128>::new();
+    engine.set_history_capacity(2);
+    for i in 0..5 {
+        engine
+            .update_with_code(&format!("o: constsig {i}"))
+            .unwrap();
+    }
+    let versions: Vec<usize> = engine.history().iter().map(|(v, _)| v).collect();
+    assert_eq!(versions, [3, 4]);
+    assert_eq!(engine.undo(), Ok(Some(3)));
+    assert_eq!(engine.undo(), Ok(None));
+}
+
+#[test]
+fn shrinking_forgets_a_trimmed_current_version() {
+    let mut engine = Engine::<128>::new();
+    for i in 0..10 {
+        engine
+            .update_with_code(&format!("o: constsig {i}"))
+            .unwrap();
+    }
+    engine.revert_to(2).unwrap();
+    engine.set_history_capacity(5);
+    assert_eq!(engine.history().current(), None);
+    assert_eq!(engine.undo(), Ok(None));
+    assert_eq!(engine.redo(), Ok(None));
+    assert_eq!(first_sample(&mut engine), 2.);
+
+    // a kept current version stays where it is
+    engine.revert_to(7).unwrap();
+    engine.set_history_capacity(3);
+    assert_eq!(engine.history().current(), Some(7));
+    assert_eq!(engine.redo(), Ok(Some(8)));
+}
+
+#[test]
+fn handles_undo_too() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(8);
+    handle.update_with_code("o: constsig 0.1").unwrap();
+    handle.update_with_code("o: constsig 0.2").unwrap();
+    assert_eq!(handle.undo(), Ok(Some(0)));
+    assert_eq!(processor.next_block(&[])[0][0], 0.1);
+}
@@ -0,0 +1,85 @@
+use glicol::*;
+
+fn samples(engine: &mut Engine<128>, blocks: usize) -> Vec<f32> {
+    (0..blocks)
+        .flat_map(|_| engine.next_block(&[])[0].to_vec())
+        .collect()
+}
+
+#[test]
+fn track_amp_is_the_master_gain() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 0.5").unwrap();
+    engine.set_track_amp(0.5);
+    assert!(samples(&mut engine, 2).iter().all(|s| *s == 0.25));
+}
+
+#[test]
+fn limiter_keeps_true_peaks_under_the_ceiling() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: sin 441 >> mul 4").unwrap();
+    engine.set_master(MasterConfig::default().limiter(true).ceiling(-6.));
+    let ceiling = 10f32.powf(-6. / 20.);
+    let out = samples(&mut engine, 100);
+    assert!(out.iter().all(|s| s.abs() <= ceiling));
+    // a little under, as the peaks between samples are counted too
+    let loudest = out[1000..].iter().fold(0f32, |max, s| max.max(s.abs()));
+    assert!(loudest > ceiling * 0.95, "{loudest}");
+}
+
+#[test]
+fn limiter_lets_quiet_signals_through_delayed() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: sin 441 >> mul 0.5").unwrap();
+    let mut dry = Engine::<128>::new();
+    dry.update_with_code("o: sin 441 >> mul 0.5").unwrap();
+    // 1ms lookahead is 44 samples, plus two for the true peak
+    engine.set_master(MasterConfig::default().limiter(true).lookahead(1.));
+    let (out, dry) = (samples(&mut engine, 4), samples(&mut dry, 4));
+    assert!(out[..46].iter().all(|s| *s == 0.));
+    assert_eq!(out[46..], dry[..512 - 46]);
+}
+
+#[test]
+fn dc_blocker_removes_offset() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 1").unwrap();
+    engine.set_master(MasterConfig::default().dc_block(true));
+    let out = samples(&mut engine, 400);
+    assert!(out[0] > 0.99);
+    assert!(out.last().unwrap().abs() < 0.01);
+}
+
+#[test]
+fn mutes_nan() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: ~input >> mul 1").unwrap();
+    engine.set_master(MasterConfig::safe());
+    let nan = [f32::NAN; 128];
+    let block = engine.next_block(&[&nan, &nan]);
+    assert!(block.iter().all(|b| b.iter().all(|s| *s == 0.)));
+
+    let ok = [0.5; 128];
+    engine.next_block(&[&ok, &ok]);
+    assert!(engine.next_block(&[&ok, &ok])[0]
+        .iter()
+        .all(|s| s.is_finite()));
+}
+
+#[test]
+fn master_survives_reset_and_split() {
+    let mut engine = Engine::<128>::new();
+    engine.set_master(MasterConfig::default().limiter(true).ceiling(-6.));
+    engine.reset();
+    let (mut handle, mut processor) = engine.split(8);
+    handle.update_with_code("o: sin 441 >> mul 4").unwrap();
+    handle.set_track_amp(2.).unwrap();
+    let peak = (0..50)
+        .flat_map(|_| processor.next_block(&[])[0].to_vec())
+        .fold(0f32, |max, s| max.max(s.abs()));
+    assert!(peak <= 10f32.powf(-6. / 20.));
+
+    handle.set_master(MasterConfig::default()).unwrap();
+    let block = processor.next_block(&[]);
+    assert!(block[0].iter().any(|s| s.abs() > 4.));
+}
@@ -0,0 +1,50 @@
+use glicol::*;
+
+fn first_samples(engine: &mut Engine<128>) -> Vec<f32> {
+    engine.next_block(&[]).iter().map(|b| b[0]).collect()
+}
+
+#[test]
+fn plays_on_the_chosen_channels() {
+    let mut engine = Engine::<128>::with_channels(4);
+    assert_eq!(engine.channels(), 4);
+    engine.update_with_code("o: sig 0.5 >> out 3").unwrap();
+    assert_eq!(first_samples(&mut engine), [0., 0., 0.5, 0.]);
+
+    engine
+        .update_with_code("o: sig 0.5 >> out 3\na: sig 0.25 >> out 1 4")
+        .unwrap();
+    assert_eq!(first_samples(&mut engine), [0.25, 0., 0.5, 0.25]);
+}
+
+#[test]
+fn other_chains_stay_on_the_first_two() {
+    let mut engine = Engine::<128>::with_channels(4);
+    engine
+        .update_with_code("o: sig 0.5\nb: sig 0.25 >> out 4")
+        .unwrap();
+    assert_eq!(first_samples(&mut engine), [0.5, 0.5, 0., 0.25]);
+}
+
+#[test]
+fn rejects_missing_channels() {
+    let mut engine = Engine::<128>::new();
+    assert!(matches!(
+        engine.update_with_code("o: sig 0.5 >> out 3"),
+        Err(EngineError::InvalidParamValue(_))
+    ));
+    assert!(matches!(
+        engine.update_with_code("o: sig 0.5 >> out 0"),
+        Err(EngineError::InvalidParamValue(_))
+    ));
+}
+
+#[test]
+fn renders_every_channel() {
+    let mut engine = Engine::<128>::with_channels(6);
+    engine.set_sr(12800);
+    engine.update_with_code("o: sig 0.5 >> out 6").unwrap();
+    let frames = engine.render(0.1);
+    assert_eq!(frames.len(), 1280 * 6);
+    assert!(frames.chunks(6).all(|f| f == [0., 0., 0., 0., 0., 0.5]));
+}
@@ -0,0 +1,149 @@
+use glicol::*;
+use glicol_synth::Node;
+
+const CODE: &str = "o: saw 110 >> lpf 1000 1.0 >> mul 0.5";
+
+#[test]
+fn set_param_matches_send_msg() {
+    let mut engine = Engine::<128>::new();
+    let mut twin = Engine::<128>::new();
+    engine.update_with_code(CODE).unwrap();
+    twin.update_with_code(CODE).unwrap();
+
+    engine
+        .set_param(
+            "o",
+            NodeSelector::Type("lpf"),
+            ParamId::Name("cutoff"),
+            300.0.into(),
+        )
+        .unwrap();
+    engine
+        .set_param(
+            "o",
+            NodeSelector::Position(2),
+            ParamId::Index(0),
+            ParamValue::Number(0.2),
+        )
+        .unwrap();
+    twin.send_msg("o,1,0,300; o,2,0,0.2");
+
+    for _ in 0..8 {
+        assert_eq!(engine.next_block(&[]), twin.next_block(&[]));
+    }
+}
+
+#[test]
+fn set_param_reports_what_is_missing() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(CODE).unwrap();
+    let set = |engine: &mut Engine<128>, chain, node, param| {
+        engine.set_param(chain, node, param, 1.0.into())
+    };
+
+    assert_eq!(
+        set(
+            &mut engine,
+            "x",
+            NodeSelector::Position(0),
+            ParamId::Index(0)
+        ),
+        Err(EngineError::NonExistReference("x".to_string()))
+    );
+    assert!(matches!(
+        set(
+            &mut engine,
+            "o",
+            NodeSelector::Position(3),
+            ParamId::Index(0)
+        ),
+        Err(EngineError::NonExistNode(_))
+    ));
+    assert!(matches!(
+        set(
+            &mut engine,
+            "o",
+            NodeSelector::Type("sin"),
+            ParamId::Index(0)
+        ),
+        Err(EngineError::NonExistNode(_))
+    ));
+    assert!(matches!(
+        set(
+            &mut engine,
+            "o",
+            NodeSelector::Type("lpf"),
+            ParamId::Name("gain")
+        ),
+        Err(EngineError::NonExistParam(_))
+    ));
+    assert!(matches!(
+        set(
+            &mut engine,
+            "o",
+            NodeSelector::Type("mul"),
+            ParamId::Index(1)
+        ),
+        Err(EngineError::NonExistParam(_))
+    ));
+}
+
+#[test]
+fn handle_sets_params() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(8);
+    handle.update_with_code("o: constsig 1 >> mul 0.5").unwrap();
+    handle
+        .set_param(
+            "o",
+            NodeSelector::Type("mul"),
+            ParamId::Name("value"),
+            0.25.into(),
+        )
+        .unwrap();
+    assert_eq!(processor.next_block(&[])[0][0], 0.25);
+    assert!(matches!(
+        handle.set_param(
+            "o",
+            NodeSelector::Type("lpf"),
+            ParamId::Name("q"),
+            1.0.into()
+        ),
+        Err(EngineError::NonExistNode(_))
+    ));
+}
+
+#[test]
+fn built_nodes_describe_their_params() {
+    let code = "o: sin 1 >> saw 1 >> squ 1 >> tri 1 >> mul 1 >> add 0 >> pan 0 \
+        >> onepole 0.9 >> delayn 1 >> delayms 1 >> lpf 300 1 >> rhpf 300 1 \
+        >> apfmsgain 10 0.5 >> envperc 0.1 0.1 >> adsr 0.1 0.1 0.3 0.1 >> plate 0.1 \
+        >> bd 0.3 >> sn 0.3 >> hh 0.03 >> sawsynth 0.1 0.1 >> squsynth 0.1 0.1 \
+        >> trisynth 0.1 0.1;
+        ~a: imp 1 >> constsig 1 >> noise 42 >> speed 1 >> choose 60 62 >> msgsynth \\saw 0.1 0.1 \
+        >> psynth `0 60` 1 >> eval `output := input;`;";
+    let ast = glicol_parser::get_ast(code).unwrap();
+    let samples = Default::default();
+    for component in ast.nodes.values().flatten() {
+        let nodes = NodeRegistry::default();
+        let (data, _) =
+            glicol::util::makenode::<128>(component, &samples, &nodes, 44100, 120., 42, 2).unwrap();
+        let params = component_params::<128>(component);
+        assert!(!params.is_empty(), "{}", component.name());
+        assert_eq!(data.node.params(), params, "{}", component.name());
+    }
+}
+
+#[test]
+fn set_param_checks_the_kind_of_value() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(CODE).unwrap();
+    assert!(matches!(
+        engine.set_param(
+            "o",
+            NodeSelector::Type("lpf"),
+            ParamId::Name("cutoff"),
+            ParamValue::Symbol("high".to_string())
+        ),
+        Err(EngineError::InvalidParamValue(_))
+    ));
+}
@@ -0,0 +1,74 @@
+use glicol::*;
+
+// the first sample of every block, at 120 bpm one beat is 22050 samples
+fn first_samples(mut next_block: impl FnMut() -> f32, blocks: usize) -> Vec<f32> {
+    (0..blocks).map(|_| next_block()).collect()
+}
+
+#[test]
+fn next_boundary() {
+    assert_eq!(Quantize::Immediate.next_boundary(0.5), None);
+    assert_eq!(Quantize::Beat.next_boundary(0.), Some(0.));
+    assert_eq!(Quantize::Beat.next_boundary(0.01), Some(1.));
+    assert_eq!(Quantize::Bar.next_boundary(0.01), Some(4.));
+    assert_eq!(Quantize::Bars(2).next_boundary(4.01), Some(8.));
+    // a rounding error past a boundary is on it
+    assert_eq!(Quantize::Bar.next_boundary(4. + 1e-12), Some(4.));
+}
+
+#[test]
+fn update_waits_for_the_beat() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 1").unwrap();
+    engine.next_block(&[]);
+
+    engine.set_quantize(Quantize::Beat);
+    engine.update_with_code("o: constsig 2").unwrap();
+    assert!(engine.has_pending_update());
+
+    // 22050 falls in the block that starts at 172 * 128
+    let samples = first_samples(|| engine.next_block(&[])[0][0], 180);
+    assert!(samples[..171].iter().all(|s| *s == 1.));
+    assert!(samples[171..].iter().all(|s| *s == 2.));
+    assert!(!engine.has_pending_update());
+}
+
+#[test]
+fn updates_stay_in_order() {
+    let mut engine = Engine::<128>::new();
+    engine.set_quantize(Quantize::Beat);
+    engine.update_with_code("o: constsig 1").unwrap();
+    engine.next_block(&[]);
+    engine.update_with_code("o: constsig 1 >> mul 2").unwrap();
+    // diffed against the waiting update, not the running program
+    engine.update_with_code("o: constsig 1 >> mul 3").unwrap();
+
+    let samples = first_samples(|| engine.next_block(&[])[0][0], 180);
+    assert!(samples[..171].iter().all(|s| *s == 1.));
+    assert!(samples[171..].iter().all(|s| *s == 3.));
+}
+
+#[test]
+fn not_livecoding_applies_immediately() {
+    let mut engine = Engine::<128>::new();
+    engine.livecoding = false;
+    engine.set_quantize(Quantize::Bar);
+    engine.update_with_code("o: constsig 1").unwrap();
+    assert!(!engine.has_pending_update());
+    assert_eq!(engine.next_block(&[])[0][0], 1.);
+}
+
+#[test]
+fn processor_waits_for_the_bar() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(4);
+    handle.update_with_code("o: constsig 1").unwrap();
+    processor.next_block(&[]);
+
+    handle.set_quantize(Quantize::Bar).unwrap();
+    handle.update_with_code("o: constsig 2").unwrap();
+
+    // 88200 falls in the block that starts at 689 * 128
+    let samples = first_samples(|| processor.next_block(&[])[0][0], 700);
+    assert!(samples[..688].iter().all(|s| *s == 1.));
+    assert!(samples[688..].iter().all(|s| *s == 2.));
+}
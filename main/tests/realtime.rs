@@ -0,0 +1,56 @@
+use glicol::*;
+
+#[test]
+fn split_matches_engine() {
+    let code = "o: sin 440 >> mul ~amp\n~amp: sin 0.5 >> mul 0.3 >> add 0.5";
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(code).unwrap();
+
+    let (mut handle, mut processor) = Engine::<128>::new().split(16);
+    handle.update_with_code(code).unwrap();
+
+    for _ in 0..8 {
+        assert_eq!(engine.next_block(&[]), processor.next_block(&[]));
+    }
+}
+
+#[test]
+fn handle_rejects_bad_code_without_queueing() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(4);
+    handle.update_with_code("o: sin 440").unwrap();
+    assert_eq!(
+        handle.update_with_code("o: sin 440 >> mul ~missing"),
+        Err(EngineError::NonExistReference("~missing".to_string()))
+    );
+    assert!(processor.next_block(&[])[0].iter().any(|s| *s != 0.));
+    assert_eq!(handle.node_count(), 1);
+}
+
+#[test]
+fn handle_reports_full_queue() {
+    let (mut handle, _processor) = Engine::<128>::new().split(1);
+    handle.update_with_code("o: sin 440").unwrap();
+    assert_eq!(
+        handle.update_with_code("o: sin 220"),
+        Err(EngineError::QueueFull)
+    );
+}
+
+#[test]
+fn processor_and_handle_are_send() {
+    fn assert_send<T: Send>() {}
+    assert_send::<EngineHandle<128>>();
+    assert_send::<EngineProcessor<128>>();
+}
+
+#[test]
+fn handle_keeps_taking_tempo_changes() {
+    let capacity = 4;
+    let (mut handle, mut processor) = Engine::<128>::new().split(capacity);
+    handle.update_with_code("o: sin 440").unwrap();
+    for i in 0..3 * capacity + 8 {
+        handle.set_bpm(100. + i as f32).unwrap();
+        processor.next_block(&[]);
+    }
+    assert_eq!(handle.get_bpm(), 100. + (3 * capacity + 7) as f32);
+}
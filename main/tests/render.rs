@@ -0,0 +1,59 @@
+use glicol::*;
+
+#[test]
+fn render_length_and_interleaving() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(r#"o: sin 440 >> pan -1"#).unwrap();
+    let samples = engine.render(0.5);
+    assert_eq!(samples.len(), 22050 * 2);
+    // hard left pan leaves the right channel silent
+    assert!(samples.chunks(2).any(|frame| frame[0] != 0.));
+    assert!(samples.chunks(2).all(|frame| frame[1].abs() < 1e-6));
+}
+
+#[test]
+fn back_to_back_renders_line_up() {
+    let code = "o: sin 440";
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(code).unwrap();
+    let mut once = Engine::<128>::new();
+    once.update_with_code(code).unwrap();
+
+    let mut samples = engine.render(0.01);
+    samples.extend(engine.render(0.01));
+    samples.extend(engine.render(0.));
+    samples.extend(engine.render(0.02));
+    assert_eq!(samples, once.render(0.04));
+}
+
+#[test]
+fn changes_drop_frames_rendered_ahead() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 1").unwrap();
+    // 441 frames leave 71 of the last block over
+    engine.render(0.01);
+    engine.update_with_code("o: constsig 0.5").unwrap();
+    assert_eq!(engine.render(0.01)[0], 0.5);
+
+    engine.render(0.01);
+    engine.pause();
+    assert_eq!(engine.render(0.01)[0], 0.);
+}
+
+#[test]
+fn no_channels_render_nothing() {
+    let mut engine = Engine::<128>::with_channels(0);
+    assert!(engine.render(0.01).is_empty());
+}
+
+#[cfg(feature = "render-wav")]
+#[test]
+fn render_wav_file() {
+    let path = std::env::temp_dir().join("glicol_render_test.wav");
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code(r#"o: sin 440"#).unwrap();
+    engine.render_to_wav(&path, 0.1).unwrap();
+    let reader = hound::WavReader::open(&path).unwrap();
+    assert_eq!(reader.spec().channels, 2);
+    assert_eq!(reader.len(), 4410 * 2);
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "load-samples")]
+
+use glicol::*;
+use std::path::{Path, PathBuf};
+
+// a fresh directory for each test, as they run in parallel
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("glicol-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// a ramp from -0.5 to 0.5 on the left and its negation on the right
+fn write_wav(path: &Path, bits: u16, float: bool, channels: u16, sr: u32) {
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate: sr,
+        bits_per_sample: bits,
+        sample_format: match float {
+            true => hound::SampleFormat::Float,
+            false => hound::SampleFormat::Int,
+        },
+    };
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    let scale = (1i64 << (bits - 1)) as f32;
+    for i in 0..100 {
+        let value = i as f32 / 99. - 0.5;
+        for c in 0..channels {
+            let value = if c == 0 { value } else { -value };
+            match (float, bits) {
+                (true, _) => writer.write_sample(value).unwrap(),
+                (false, 8) => writer.write_sample((value * scale) as i8).unwrap(),
+                (false, 16) => writer.write_sample((value * scale) as i16).unwrap(),
+                (false, _) => writer.write_sample((value * scale) as i32).unwrap(),
+            }
+        }
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn decodes_every_wav_format() {
+    let dir = temp_dir("formats");
+    for (bits, float) in [
+        (8, false),
+        (16, false),
+        (24, false),
+        (32, false),
+        (32, true),
+    ] {
+        let path = dir.join(format!("{bits}{float}.wav"));
+        write_wav(&path, bits, float, 2, 44100);
+        let sample = decode_sample_file(&path, 44100).unwrap();
+        assert_eq!(
+            (sample.channels, sample.sr, sample.frames()),
+            (2, 44100, 100)
+        );
+        // planar, left then right
+        assert!((sample.data[0] + 0.5).abs() < 0.01, "{bits} {float}");
+        assert!((sample.data[99] - 0.5).abs() < 0.01, "{bits} {float}");
+        assert!((sample.data[100] - 0.5).abs() < 0.01, "{bits} {float}");
+    }
+}
+
+#[test]
+fn resamples_to_the_engine_rate() {
+    let dir = temp_dir("resample");
+    let path = dir.join("ramp.wav");
+    write_wav(&path, 32, true, 1, 22050);
+    let sample = decode_sample_file(&path, 44100).unwrap();
+    assert_eq!(
+        (sample.channels, sample.sr, sample.frames()),
+        (1, 44100, 200)
+    );
+    // a ramp stays a ramp
+    assert!((sample.data[0] + 0.5).abs() < 1e-6);
+    assert!((sample.data[101] - (50.5 / 99. - 0.5)).abs() < 1e-5);
+}
+
+#[test]
+fn loads_a_directory() {
+    let dir = temp_dir("dir");
+    write_wav(&dir.join("808bd.wav"), 16, false, 1, 44100);
+    write_wav(&dir.join("clap.WAV"), 16, false, 2, 48000);
+    std::fs::write(dir.join("notes.txt"), "not audio").unwrap();
+
+    let mut engine = Engine::<128>::new();
+    let names = engine.load_sample_dir(&dir).unwrap();
+    assert_eq!(names, [r"\808bd", r"\clap"]);
+    assert_eq!(engine.samples().get(r"\clap").unwrap().sr, 44100);
+    engine
+        .update_with_code(r"o: imp 1 >> sp \808bd; c: imp 1 >> sp \clap")
+        .unwrap();
+}
+
+#[test]
+fn reports_unreadable_files() {
+    let dir = temp_dir("broken");
+    std::fs::write(dir.join("broken.wav"), "not audio").unwrap();
+    let mut engine = Engine::<128>::new();
+    assert!(matches!(
+        engine.load_sample_file(r"\broken", dir.join("broken.wav")),
+        Err(EngineError::SampleFile(_))
+    ));
+    assert!(matches!(
+        engine.load_sample_dir(dir.join("missing")),
+        Err(EngineError::SampleFile(_))
+    ));
+    assert!(engine.samples().is_empty());
+}
@@ -0,0 +1,74 @@
+use glicol::*;
+
+const CODE: &str = "o: imp 2 >> bd 0.2 >> plate 0.1
+a: saw 220 >> lpf 800 1 >> rhpf 100 1 >> delayms 3 >> mul 0.3
+b: seq 60 _ 62 >> sawsynth 0.01 0.1 >> pan 0.2
+c: imp 4 >> sn 0.1 >> apfmsgain 5 0.5
+d: imp 3 >> hh 0.05 >> envperc 0.01 0.1";
+
+#[test]
+fn changing_sr_matches_building_at_it() {
+    for sr in [48000, 96000] {
+        let mut changed = Engine::<128>::new();
+        changed.update_with_code(CODE).unwrap();
+        changed.set_sr(sr);
+
+        let mut built = Engine::<128>::new();
+        built.set_sr(sr);
+        built.update_with_code(CODE).unwrap();
+
+        for _ in 0..200 {
+            let changed = changed.next_block(&[]).to_vec();
+            let built = built.next_block(&[]);
+            // chains are summed in no particular order
+            for (a, b) in changed.iter().zip(built) {
+                assert!(a
+                    .iter()
+                    .zip(b.iter())
+                    .all(|(a, b)| (a - b).abs() <= 1e-5 * a.abs().max(1.)));
+            }
+        }
+    }
+}
+
+#[test]
+fn sounds_the_same_at_any_sr() {
+    // a 441 Hz sine has its first peak a 1764th of a second in
+    let peak = |sr: usize| {
+        let mut engine = Engine::<128>::new();
+        engine.update_with_code("o: sin 441").unwrap();
+        engine.set_sr(sr);
+        engine.next_block(&[])[0][sr / 1764]
+    };
+    for sr in [44100, 88200, 176400] {
+        assert!((peak(sr) - 1.).abs() < 1e-3, "{sr}");
+    }
+}
+
+#[test]
+fn keeps_the_position() {
+    let mut engine = Engine::<128>::new();
+    engine
+        .update_with_code("o: seq 60 >> sawsynth 0.01 0.1")
+        .unwrap();
+    for _ in 0..100 {
+        engine.next_block(&[]);
+    }
+    let bars = engine.position().bars;
+    engine.set_sr(96000);
+    assert!((engine.position().bars - bars).abs() < 1e-4);
+}
+
+#[test]
+fn prepared_updates_follow_the_new_sr() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: sin 441").unwrap();
+    let mut update = engine.prepare("o: sin 441 >> mul 0.5").unwrap();
+    engine.set_sr(88200);
+    engine.apply(&mut update);
+
+    let mut built = Engine::<128>::new();
+    built.set_sr(88200);
+    built.update_with_code("o: sin 441 >> mul 0.5").unwrap();
+    assert_eq!(engine.next_block(&[]), built.next_block(&[]));
+}
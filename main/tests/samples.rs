@@ -0,0 +1,109 @@
+#![cfg(feature = "use-samples")]
+
+use glicol::*;
+use glicol_synth::Buffer;
+use std::sync::Arc;
+
+const CODE: &str = r"o: imp 10 >> sp \s";
+
+fn engine_with(value: f32) -> Engine<128> {
+    let mut engine = Engine::<128>::new();
+    engine
+        .add_sample(r"\s", vec![value; 44100], 1, 44100)
+        .unwrap();
+    engine.update_with_code(CODE).unwrap();
+    engine
+}
+
+fn plays(block: &[Buffer<128>], value: f32) -> bool {
+    block[0].iter().all(|s| *s == value)
+}
+
+#[test]
+fn replacing_a_sample_swaps_it_in_running_nodes() {
+    let mut engine = engine_with(0.5);
+    assert!(plays(engine.next_block(&[]), 0.5));
+    let old = engine.samples().get(r"\s").unwrap().clone();
+
+    engine
+        .add_sample(r"\s", vec![0.25; 44100], 1, 44100)
+        .unwrap();
+    assert!(plays(engine.next_block(&[]), 0.25));
+    assert_eq!(Arc::strong_count(&old.data), 1);
+}
+
+#[test]
+fn removed_samples_are_freed_with_their_nodes() {
+    let mut engine = engine_with(0.5);
+    let removed = engine.remove_sample(r"\s").unwrap();
+    assert!(engine.samples().is_empty());
+    // still playing
+    assert!(plays(engine.next_block(&[]), 0.5));
+    assert_eq!(Arc::strong_count(&removed.data), 2);
+
+    assert_eq!(
+        engine.update_with_code(r"b: imp 10 >> sp \s"),
+        Err(EngineError::NonExistSample(r"\s".to_string()))
+    );
+    engine.update_with_code("o: sin 440").unwrap();
+    assert_eq!(Arc::strong_count(&removed.data), 1);
+}
+
+#[test]
+fn psampler_follows_replaced_samples() {
+    let mut engine = Engine::<128>::new();
+    engine
+        .add_sample(r"\s", vec![0.5; 44100], 1, 44100)
+        .unwrap();
+    engine
+        .update_with_code(r#"o: psampler "\s@0.0"(1)"#)
+        .unwrap();
+    assert!(plays(engine.next_block(&[]), 0.5));
+
+    engine
+        .add_sample(r"\s", vec![0.25; 44100], 1, 44100)
+        .unwrap();
+    engine.seek(0.);
+    assert!(plays(engine.next_block(&[]), 0.25));
+}
+
+#[test]
+fn handle_frees_replaced_samples_on_its_side() {
+    let (mut handle, mut processor) = engine_with(0.5).split(8);
+    processor.next_block(&[]);
+    let old = handle.samples().get(r"\s").unwrap().clone();
+
+    handle
+        .add_sample(r"\s", vec![0.25; 44100], 1, 44100)
+        .unwrap();
+    assert!(plays(processor.next_block(&[]), 0.25));
+    // the handle and the retired sample on its way back
+    assert_eq!(Arc::strong_count(&old.data), 2);
+    handle.collect_garbage();
+    assert_eq!(Arc::strong_count(&old.data), 1);
+}
+
+#[test]
+fn rejects_samples_the_samplers_cant_play() {
+    let mut engine = engine_with(0.5);
+    for (data, channels) in [
+        (vec![], 1),
+        (vec![0.5; 10], 0),
+        (vec![0.5; 9], 2),
+        (vec![0.5; 9], 3),
+    ] {
+        assert!(matches!(
+            engine.add_sample(r"\s", data, channels, 44100),
+            Err(EngineError::InvalidSample(_))
+        ));
+    }
+    // the running sample stays
+    assert!(plays(engine.next_block(&[]), 0.5));
+
+    let (mut handle, _processor) = engine.split(8);
+    assert!(matches!(
+        handle.add_sample(r"\s", vec![], 1, 44100),
+        Err(EngineError::InvalidSample(_))
+    ));
+    assert_eq!(handle.samples().get(r"\s").unwrap().data[0], 0.5);
+}
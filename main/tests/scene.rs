@@ -0,0 +1,98 @@
+use glicol::*;
+
+fn first_sample(engine: &mut Engine<128>) -> f32 {
+    engine.next_block(&[])[0][0]
+}
+
+fn scenes() -> Engine<128> {
+    let mut engine = Engine::<128>::new();
+    engine.livecoding = false;
+    engine.add_scene("explore", "o: constsig 0.1").unwrap();
+    engine.add_scene("combat", "o: constsig 0.2").unwrap();
+    engine
+}
+
+#[test]
+fn switches_on_the_next_bar() {
+    let mut engine = scenes();
+    engine
+        .switch_scene("explore", Quantize::Immediate, 0.)
+        .unwrap();
+    assert_eq!(first_sample(&mut engine), 0.1);
+
+    // a bar at 120 bpm is 88200 samples, in the block starting at 88192
+    engine.switch_scene("combat", Quantize::Bar, 0.).unwrap();
+    assert_eq!(engine.current_scene(), Some("combat"));
+    let explore = (1..1000)
+        .take_while(|_| first_sample(&mut engine) == 0.1)
+        .count();
+    assert_eq!(explore, 688);
+}
+
+#[test]
+fn shared_chains_keep_playing() {
+    let shared = "a: sin 3 >> mul 0.1";
+    let mut engine = Engine::<128>::new();
+    engine
+        .add_scene("explore", &format!("{shared}\nb: constsig 0.1"))
+        .unwrap();
+    engine.add_scene("combat", shared).unwrap();
+    engine
+        .switch_scene("explore", Quantize::Immediate, 0.)
+        .unwrap();
+    let mut reference = Engine::<128>::new();
+    reference.update_with_code(shared).unwrap();
+    for _ in 0..10 {
+        engine.next_block(&[]);
+        reference.next_block(&[]);
+    }
+
+    engine
+        .switch_scene("combat", Quantize::Immediate, 0.)
+        .unwrap();
+    for _ in 0..10 {
+        assert_eq!(engine.next_block(&[]), reference.next_block(&[]));
+    }
+}
+
+#[test]
+fn other_code_leaves_the_scene() {
+    let mut engine = scenes();
+    engine
+        .switch_scene("explore", Quantize::Immediate, 0.)
+        .unwrap();
+    engine.update_with_code("o: constsig 0.3").unwrap();
+    assert_eq!(engine.current_scene(), None);
+    assert_eq!(engine.undo(), Ok(Some(0)));
+    assert_eq!(first_sample(&mut engine), 0.1);
+}
+
+#[test]
+fn checks_names_and_code() {
+    let mut engine = scenes();
+    assert_eq!(
+        engine.switch_scene("boss", Quantize::Bar, 0.),
+        Err(EngineError::NonExistScene("boss".to_string()))
+    );
+    assert!(engine.add_scene("boss", "o: sin >>").is_err());
+    let mut names: Vec<&str> = engine.scenes().collect();
+    names.sort();
+    assert_eq!(names, ["combat", "explore"]);
+}
+
+#[test]
+fn handles_switch_scenes() {
+    let (mut handle, mut processor) = scenes().split(8);
+    handle.add_scene("boss", "o: constsig 0.3").unwrap();
+    handle
+        .switch_scene("explore", Quantize::Immediate, 0.)
+        .unwrap();
+    assert_eq!(processor.next_block(&[])[0][0], 0.1);
+    handle.switch_scene("boss", Quantize::Beat, 0.).unwrap();
+    assert_eq!(handle.current_scene(), Some("boss"));
+    let explore = (1..1000)
+        .take_while(|_| processor.next_block(&[])[0][0] == 0.1)
+        .count();
+    // a beat is 22050 samples, in the block starting at 21888
+    assert_eq!(explore, 171);
+}
@@ -0,0 +1,114 @@
+use glicol::*;
+
+// the first sample that is not silent, counted from now
+fn first_sound(mut next_block: impl FnMut() -> Vec<f32>) -> usize {
+    let mut samples = 0;
+    loop {
+        let block = next_block();
+        if let Some(i) = block.iter().position(|s| *s != 0.) {
+            return samples + i;
+        }
+        samples += block.len();
+        assert!(samples < 441000, "never played");
+    }
+}
+
+#[test]
+fn notes_start_on_their_sample() {
+    let mut engine = Engine::<128>::new();
+    engine
+        .update_with_code(r"o: msgsynth \saw 0.001 0.1")
+        .unwrap();
+    engine
+        .schedule(
+            EventTime::Samples(300),
+            "o",
+            NodeSelector::Type("msgsynth"),
+            Message::Note(60.),
+        )
+        .unwrap();
+    assert_eq!(engine.scheduled_len(), 1);
+    assert_eq!(first_sound(|| engine.next_block(&[])[0].to_vec()), 300);
+    assert_eq!(engine.scheduled_len(), 0);
+}
+
+#[test]
+fn beats_keep_their_place_when_the_tempo_changes() {
+    let mut engine = Engine::<128>::new();
+    engine
+        .update_with_code(r"o: msgsynth \saw 0.001 0.1")
+        .unwrap();
+    engine
+        .schedule(
+            EventTime::Beats(2.),
+            "o",
+            NodeSelector::Position(0),
+            Message::Note(60.),
+        )
+        .unwrap();
+    engine.set_bpm(60.);
+    // two beats at 60 bpm
+    assert_eq!(first_sound(|| engine.next_block(&[])[0].to_vec()), 88200);
+}
+
+#[cfg(feature = "use-samples")]
+#[test]
+fn triggers_play_samples_once() {
+    let mut engine = Engine::<128>::new();
+    engine.add_sample(r"\s", vec![0.5; 1000], 1, 44100).unwrap();
+    engine.update_with_code(r"o: sp \s").unwrap();
+    for at in [1000, 5000] {
+        engine
+            .schedule(
+                EventTime::Samples(at),
+                "o",
+                NodeSelector::Type("sp"),
+                Message::Trigger(1.),
+            )
+            .unwrap();
+    }
+    let mut played = vec![];
+    for _ in 0..50 {
+        played.extend_from_slice(&engine.next_block(&[])[0]);
+    }
+    let playing: Vec<bool> = played.iter().map(|s| *s != 0.).collect();
+    assert_eq!(playing.iter().position(|p| *p), Some(1000));
+    assert_eq!(playing[1000..=2000].iter().filter(|p| **p).count(), 1001);
+    assert!(!playing[2001..5000].iter().any(|p| *p));
+    assert!(playing[5000]);
+}
+
+#[test]
+fn checks_the_target() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: sin 440").unwrap();
+    let note = |engine: &mut Engine<128>, chain, node| {
+        engine.schedule(EventTime::Beats(1.), chain, node, Message::Note(60.))
+    };
+    assert_eq!(
+        note(&mut engine, "a", NodeSelector::Position(0)),
+        Err(EngineError::NonExistReference("a".to_string()))
+    );
+    assert!(note(&mut engine, "o", NodeSelector::Type("lpf")).is_err());
+    assert_eq!(engine.scheduled_len(), 0);
+}
+
+#[test]
+fn handles_schedule_notes() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(8);
+    handle
+        .update_with_code(r"o: msgsynth \saw 0.001 0.1")
+        .unwrap();
+    handle
+        .schedule(
+            EventTime::Samples(1000),
+            "o",
+            NodeSelector::Position(0),
+            Message::Note(60.),
+        )
+        .unwrap();
+    assert_eq!(
+        first_sound(|| processor.next_block(&[])[0].to_vec()),
+        1000
+    );
+}
@@ -0,0 +1,69 @@
+use glicol::*;
+
+const CODE: &str = "a: noise 42 >> mul 0.3
+b: noise 42 >> lpf 1000 1 >> mul 0.3
+h: imp 8 >> hh 0.03
+s: imp 4 >> sn 0.1
+c: choose 1 2 3 >> mul 0.1";
+
+fn render(engine: &mut Engine<128>, blocks: usize) -> Vec<f32> {
+    (0..blocks)
+        .flat_map(|_| {
+            let block = engine.next_block(&[]);
+            [block[0].to_vec(), block[1].to_vec()].concat()
+        })
+        .collect()
+}
+
+fn engine_with(seed: usize, code: &str) -> Engine<128> {
+    let mut engine = Engine::<128>::new();
+    engine.set_seed(seed);
+    engine.update_with_code(code).unwrap();
+    engine
+}
+
+#[test]
+fn same_seed_is_bit_identical() {
+    let a = render(&mut engine_with(7, CODE), 50);
+    let b = render(&mut engine_with(7, CODE), 50);
+    assert_eq!(a, b);
+    assert!(a.iter().any(|s| *s != 0.));
+}
+
+#[test]
+fn other_seeds_vary_reproducibly() {
+    let a = render(&mut engine_with(7, CODE), 10);
+    let b = render(&mut engine_with(8, CODE), 10);
+    assert_ne!(a, b);
+    assert_eq!(b, render(&mut engine_with(8, CODE), 10));
+}
+
+#[test]
+fn each_node_gets_its_own_seed() {
+    let a = render(&mut engine_with(7, "a: noise 42"), 2);
+    let b = render(&mut engine_with(7, "b: noise 42"), 2);
+    assert_ne!(a, b);
+    let both = render(&mut engine_with(7, "a: noise 42 >> mul 1 >> noise 42"), 2);
+    assert_ne!(a, both);
+}
+
+// no state but the random generators, so restarting them starts over
+const NOISE: &str = "a: noise 42 >> mul 0.3
+c: choose 1 2 3 >> mul 0.1";
+
+#[test]
+fn running_nodes_restart_from_the_new_seed() {
+    let mut engine = engine_with(7, NOISE);
+    render(&mut engine, 3);
+    engine.set_seed(8);
+    let (mut handle, mut processor) = engine_with(7, NOISE).split(8);
+    handle.set_seed(8).unwrap();
+    let split: Vec<f32> = (0..10)
+        .flat_map(|_| {
+            let block = processor.next_block(&[]);
+            [block[0].to_vec(), block[1].to_vec()].concat()
+        })
+        .collect();
+    assert_eq!(render(&mut engine, 10), split);
+    assert_eq!(split, render(&mut engine_with(8, NOISE), 10));
+}
@@ -0,0 +1,106 @@
+use glicol::*;
+
+fn set_mul(engine: &mut Engine<128>, value: f32) {
+    engine
+        .set_param(
+            "o",
+            NodeSelector::Type("mul"),
+            ParamId::Name("value"),
+            value.into(),
+        )
+        .unwrap();
+}
+
+fn samples(engine: &mut Engine<128>, blocks: usize) -> Vec<f32> {
+    (0..blocks)
+        .flat_map(|_| engine.next_block(&[])[0].to_vec())
+        .collect()
+}
+
+#[test]
+fn jumps_without_smoothing() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 1 >> mul 0").unwrap();
+    engine.next_block(&[]);
+    set_mul(&mut engine, 1.);
+    assert!(samples(&mut engine, 1).iter().all(|s| *s == 1.));
+}
+
+#[test]
+fn linear_ramp() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 1 >> mul 0").unwrap();
+    engine.set_smoothing(Smoothing::Linear(10.));
+    engine.next_block(&[]);
+
+    // 10ms at 44100 is 441 samples
+    set_mul(&mut engine, 1.);
+    let ramp = samples(&mut engine, 4);
+    assert!((ramp[0] - 1. / 441.).abs() < 1e-6);
+    assert!((ramp[219] - 220. / 441.).abs() < 1e-4);
+    assert!(ramp[..440].windows(2).all(|w| w[0] < w[1]));
+    assert!(ramp[440..].iter().all(|s| *s == 1.));
+}
+
+#[test]
+fn new_nodes_and_code_updates_are_smoothed() {
+    let mut engine = Engine::<128>::new();
+    engine.set_smoothing(Smoothing::OnePole(5.));
+    engine.update_with_code("o: constsig 1 >> mul 0").unwrap();
+    engine.next_block(&[]);
+
+    engine.update_with_code("o: constsig 1 >> mul 1").unwrap();
+    let ramp = samples(&mut engine, 20);
+    assert!(ramp[0] > 0. && ramp[0] < 0.01);
+    assert!(ramp.windows(2).all(|w| w[0] <= w[1]));
+    // about 3.5 time constants
+    assert!((ramp[770] - 0.97).abs() < 0.01);
+    assert_eq!(ramp.last(), Some(&1.));
+}
+
+#[test]
+fn smoothing_one_param() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 1 >> mul 0").unwrap();
+    engine
+        .set_param_smoothing(
+            "o",
+            NodeSelector::Type("mul"),
+            ParamId::Index(0),
+            Smoothing::Linear(1.),
+        )
+        .unwrap();
+    engine.next_block(&[]);
+    set_mul(&mut engine, 1.);
+    let ramp = samples(&mut engine, 1);
+    assert!(ramp[0] < 0.1);
+    assert_eq!(ramp[127], 1.);
+
+    assert!(matches!(
+        engine.set_param_smoothing(
+            "o",
+            NodeSelector::Type("constsig"),
+            ParamId::Index(0),
+            Smoothing::Linear(1.),
+        ),
+        Err(EngineError::InvalidParamValue(_))
+    ));
+}
+
+#[test]
+fn handle_sets_smoothing() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(8);
+    handle.update_with_code("o: constsig 1 >> mul 0").unwrap();
+    handle.set_smoothing(Smoothing::Linear(10.)).unwrap();
+    processor.next_block(&[]);
+    handle
+        .set_param(
+            "o",
+            NodeSelector::Type("mul"),
+            ParamId::Name("value"),
+            1.0.into(),
+        )
+        .unwrap();
+    let block = processor.next_block(&[]);
+    assert!((block[0][127] - 128. / 441.).abs() < 1e-4);
+}
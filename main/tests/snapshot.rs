@@ -0,0 +1,133 @@
+use glicol::*;
+
+fn block(engine: &mut Engine<128>) -> Vec<f32> {
+    engine.next_block(&[])[0].to_vec()
+}
+
+fn session() -> Engine<128> {
+    let mut engine = Engine::<128>::new();
+    engine
+        .update_with_code("o: constsig 0.2 >> mul 0.5\n~a: noise 1")
+        .unwrap();
+    engine.set_bpm(90.);
+    engine.set_seed(7);
+    engine.set_track_amp(0.5);
+    engine.send_msg("o,0,0,0.8;o,1,0,0.25");
+    for _ in 0..10 {
+        engine.next_block(&[]);
+    }
+    engine
+}
+
+#[test]
+fn captures_what_messages_changed() {
+    let snapshot = session().snapshot();
+    assert_eq!(snapshot.code, "o: constsig 0.2 >> mul 0.5\n~a: noise 1");
+    assert_eq!(
+        (snapshot.bpm, snapshot.seed, snapshot.track_amp),
+        (90., 7, 0.5)
+    );
+    assert_eq!(snapshot.position, 1280);
+    assert_eq!(snapshot.transport, TransportState::Playing);
+    assert_eq!(
+        snapshot.params,
+        [
+            ParamOverride {
+                chain: "o".to_string(),
+                position: 0,
+                index: 0,
+                value: ParamValue::Number(0.8),
+            },
+            ParamOverride {
+                chain: "o".to_string(),
+                position: 1,
+                index: 0,
+                value: ParamValue::Number(0.25),
+            },
+        ]
+    );
+}
+
+#[test]
+fn restores_into_a_new_engine() {
+    let mut engine = session();
+    let snapshot = engine.snapshot();
+
+    let mut restored = Engine::<128>::new();
+    restored.restore(&snapshot).unwrap();
+    assert_eq!(restored.snapshot(), snapshot);
+    for _ in 0..4 {
+        assert_eq!(block(&mut restored), block(&mut engine));
+    }
+    assert_eq!(block(&mut restored)[0], 0.8 * 0.25 * 0.5);
+}
+
+#[test]
+fn restores_over_a_running_program() {
+    let mut engine = session();
+    let snapshot = engine.snapshot();
+
+    let mut other = Engine::<128>::new();
+    other
+        .update_with_code("o: constsig 0.2 >> mul 0.5")
+        .unwrap();
+    other.send_msg("o,1,0,1");
+    other.pause();
+    other.restore(&snapshot).unwrap();
+    assert_eq!(other.snapshot(), snapshot);
+    assert_eq!(block(&mut other), block(&mut engine));
+}
+
+#[test]
+fn keeps_the_position_in_time() {
+    let mut session = Engine::<128>::new();
+    session.set_sr(22050);
+    session.update_with_code("o: constsig 0.2").unwrap();
+    for _ in 0..10 {
+        session.next_block(&[]);
+    }
+    let snapshot = session.snapshot();
+    assert_eq!((snapshot.position, snapshot.sr), (1280, 22050));
+    let mut engine = Engine::<128>::new();
+    engine.restore(&snapshot).unwrap();
+    assert_eq!(engine.position().samples, 2560);
+}
+
+#[test]
+fn bad_code_changes_nothing() {
+    let mut engine = session();
+    let before = engine.snapshot();
+    let mut snapshot = before.clone();
+    snapshot.code = "o: sin 440 >>".to_string();
+    snapshot.bpm = 60.;
+    assert!(engine.restore(&snapshot).is_err());
+    assert_eq!(engine.snapshot(), before);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serializes() {
+    let snapshot = session().snapshot();
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert_eq!(serde_json::from_str::<Snapshot>(&json).unwrap(), snapshot);
+}
+
+#[test]
+fn restores_a_tempo_ramp() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: seq 60").unwrap();
+    engine.ramp_bpm(180., 1.);
+    for _ in 0..100 {
+        engine.next_block(&[]);
+    }
+    let snapshot = engine.snapshot();
+
+    let mut restored = Engine::<128>::new();
+    restored.restore(&snapshot).unwrap();
+    assert_eq!(restored.snapshot(), snapshot);
+    for _ in 0..1000 {
+        assert_eq!(block(&mut restored), block(&mut engine));
+        assert_eq!(restored.get_bpm(), engine.get_bpm());
+    }
+    assert_eq!(restored.get_bpm(), 180.);
+}
@@ -0,0 +1,78 @@
+use glicol::*;
+
+#[test]
+fn levels_follow_the_chain() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 0.5").unwrap();
+    let tap = engine.tap("o");
+    for _ in 0..400 {
+        engine.next_block(&[]);
+    }
+    assert_eq!(tap.channels(), 2);
+    assert_eq!(tap.peak(), [0.5, 0.5]);
+    // reset by reading
+    assert_eq!(tap.peak(), [0., 0.]);
+    assert!(tap.rms().iter().all(|rms| (rms - 0.5).abs() < 0.01));
+}
+
+#[test]
+fn snapshot_is_the_latest_output() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: sin 441").unwrap();
+    let tap = engine.tap("o");
+    let mut out = vec![];
+    for _ in 0..10 {
+        out.extend_from_slice(&engine.next_block(&[])[0]);
+    }
+    let snapshot = tap.snapshot(256);
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot[0], out[1280 - 256..]);
+    assert_eq!(snapshot[1], out[1280 - 256..]);
+    assert_eq!(tap.snapshot(100_000)[0].len(), 1280);
+}
+
+#[test]
+fn taps_chains_nobody_hears() {
+    let mut engine = Engine::<128>::new();
+    engine
+        .update_with_code("~bass: sin 100\no: ~bass >> mul 0")
+        .unwrap();
+    let bass = engine.tap("~bass");
+    let missing = engine.tap("~missing");
+    for _ in 0..10 {
+        engine.next_block(&[]);
+    }
+    assert!(bass.peak()[0] > 0.9);
+    assert_eq!(missing.peak(), [0., 0.]);
+    assert!(missing.snapshot(128).concat().iter().all(|s| *s == 0.));
+}
+
+#[test]
+fn handle_taps_the_audio_side() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 0.5").unwrap();
+    let before = engine.tap("o");
+    let (mut handle, mut processor) = engine.split(8);
+    let after = handle.tap("o").unwrap();
+    drop(before);
+    let again = handle.tap("o").unwrap();
+    processor.next_block(&[]);
+    assert_eq!(after.peak(), [0.5, 0.5]);
+    assert_eq!(again.snapshot(1), [[0.5], [0.5]]);
+}
+
+#[test]
+fn reads_the_channels_a_chain_goes_to() {
+    let mut engine = Engine::<128>::with_channels(4);
+    engine
+        .update_with_code("o: sig 0.5 >> out 3 4
+a: sig 0.25")
+        .unwrap();
+    let o = engine.tap("o");
+    let a = engine.tap("a");
+    engine.next_block(&[]);
+    assert_eq!(o.channels(), 4);
+    assert_eq!(o.peak(), [0., 0., 0.5, 0.5]);
+    assert_eq!(o.snapshot(1), [[0.], [0.], [0.5], [0.5]]);
+    assert_eq!(a.peak(), [0.25, 0.25, 0., 0.]);
+}
@@ -0,0 +1,125 @@
+use glicol::*;
+
+#[test]
+fn set_bpm_keeps_the_place_in_the_bar() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: seq 60").unwrap();
+    for _ in 0..100 {
+        engine.next_block(&[]);
+    }
+    let before = engine.position().bars;
+    engine.set_bpm(90.);
+    assert!((engine.position().bars - before).abs() < 1e-4);
+}
+
+// where the triggers of a `seq` on every bar line fall during a ramp, in bars counted by
+// the tempo of each block
+fn bar_triggers(bpm: f32, bars: f32, count: usize) -> Vec<f64> {
+    let sr = 44100.;
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: seq 60").unwrap();
+    for _ in 0..10 {
+        engine.next_block(&[]);
+    }
+    engine.ramp_bpm(bpm, bars);
+
+    let mut bars = engine.position().bars;
+    let mut triggers = vec![];
+    while triggers.len() < count {
+        let block = engine.next_block(&[])[0].to_vec();
+        let per_sample = engine.get_bpm() as f64 / 240. / sr;
+        for (i, sample) in block.iter().enumerate() {
+            if *sample > 0. {
+                triggers.push(bars + i as f64 * per_sample);
+            }
+        }
+        bars += 128. * per_sample;
+    }
+    assert_eq!(engine.get_bpm(), bpm);
+    triggers
+}
+
+// within two samples of the bar line at up to 180 bpm: a trigger falls on a whole sample,
+// and the grid is rounded to one when the tempo changes
+fn assert_on_the_grid(triggers: &[f64]) {
+    for trigger in triggers {
+        let off_grid = trigger.fract().min(1. - trigger.fract());
+        assert!(off_grid < 2. * 180. / 240. / 44100., "{trigger}");
+    }
+}
+
+#[test]
+fn seq_stays_on_the_grid_through_a_ramp() {
+    assert_on_the_grid(&bar_triggers(180., 4., 8));
+}
+
+#[test]
+fn long_ramps_do_not_drift() {
+    let triggers = bar_triggers(180., 64., 80);
+    assert_on_the_grid(&triggers);
+    // and none is skipped or doubled
+    for pair in triggers.windows(2) {
+        assert!((pair[1] - pair[0] - 1.).abs() < 0.001, "{pair:?}");
+    }
+}
+
+#[test]
+fn ramps_over_bars() {
+    let mut engine = Engine::<128>::new();
+    engine.set_sr(12800);
+    engine.ramp_bpm(60., 1.);
+    // a bar takes between 2 and 4 seconds
+    engine.render(2.);
+    assert!(engine.get_bpm() > 60. && engine.get_bpm() < 120.);
+    engine.render(2.);
+    assert_eq!(engine.get_bpm(), 60.);
+    engine.set_bpm(100.);
+    engine.render(1.);
+    assert_eq!(engine.get_bpm(), 100.);
+}
+
+#[test]
+fn maps_tempo_by_bar() {
+    let map = TempoMap::new(120.).ramp(4., 180.).jump(8., 90.);
+    assert_eq!(map.bpm_at(0.), 120.);
+    assert_eq!(map.bpm_at(2.), 150.);
+    assert_eq!(map.bpm_at(6.), 180.);
+    assert_eq!(map.bpm_at(8.), 90.);
+    assert_eq!(map.bpm_at(100.), 90.);
+
+    let mut engine = Engine::<128>::new();
+    engine.set_sr(12800);
+    engine.set_tempo_map(map.clone());
+    // 4 bars of 2 to 1.33 seconds, then 4 bars of 1.33
+    engine.render(10.);
+    assert_eq!(engine.get_bpm(), 180.);
+    engine.render(4.);
+    assert_eq!(engine.get_bpm(), 90.);
+    assert_eq!(engine.tempo_map(), Some(&map));
+}
+
+#[test]
+fn waiting_updates_keep_their_bar() {
+    let mut engine = Engine::<128>::new();
+    engine.set_quantize(Quantize::Bar);
+    engine.update_with_code("o: constsig 0.1").unwrap();
+    engine.next_block(&[]);
+    engine.update_with_code("o: constsig 0.2").unwrap();
+    engine.set_bpm(60.);
+    while engine.has_pending_update() {
+        assert!(engine.position().bars < 1.);
+        engine.next_block(&[]);
+    }
+    assert!(engine.position().bars - 1. < 128. / 44100.);
+}
+
+#[test]
+fn handles_ramp_the_tempo() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(8);
+    handle.update_with_code("o: seq 60").unwrap();
+    handle.ramp_bpm(240., 0.5).unwrap();
+    handle.set_tempo_map(TempoMap::new(60.)).unwrap();
+    handle.set_bpm(100.).unwrap();
+    processor.next_block(&[]);
+    assert_eq!(handle.get_bpm(), 100.);
+}
@@ -0,0 +1,71 @@
+use glicol::*;
+
+// at 120 bpm a bar is 88200 samples
+const BAR: usize = 88200;
+
+fn first_impulse<const N: usize>(engine: &mut Engine<N>, blocks: usize) -> Option<usize> {
+    let start = engine.position().samples;
+    (0..blocks).find_map(|block| {
+        let buf = engine.next_block(&[]);
+        let i = buf[0].iter().position(|s| *s != 0.)?;
+        Some(start + block * N + i)
+    })
+}
+
+#[test]
+fn new_chain_follows_the_song_position() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 0").unwrap();
+    for _ in 0..100 {
+        engine.next_block(&[]);
+    }
+    engine.update_with_code("o: seq 60").unwrap();
+    assert_eq!(first_impulse(&mut engine, 700), Some(BAR));
+}
+
+#[test]
+fn pause_keeps_the_position() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: constsig 1").unwrap();
+    engine.next_block(&[]);
+
+    engine.pause();
+    assert_eq!(engine.transport_state(), TransportState::Paused);
+    assert!(engine.next_block(&[])[0].iter().all(|s| *s == 0.));
+    assert_eq!(engine.position().samples, 128);
+
+    engine.play();
+    assert!(engine.next_block(&[])[0].iter().all(|s| *s == 1.));
+    assert_eq!(engine.position().samples, 256);
+}
+
+#[test]
+fn seek_and_stop() {
+    let mut engine = Engine::<128>::new();
+    engine.update_with_code("o: seq 60").unwrap();
+    engine.next_block(&[]);
+
+    engine.seek(2.);
+    assert_eq!(engine.position().samples, 2 * BAR);
+    assert_eq!(engine.position().bars, 2.);
+    assert_eq!(engine.position().beats, 8.);
+    assert_eq!(engine.next_block(&[])[0][0], 1.);
+
+    engine.stop();
+    assert_eq!(engine.transport_state(), TransportState::Stopped);
+    assert_eq!(engine.position().samples, 0);
+    assert!(engine.next_block(&[])[0].iter().all(|s| *s == 0.));
+
+    engine.play();
+    assert_eq!(engine.next_block(&[])[0][0], 1.);
+}
+
+#[test]
+fn handle_controls_the_transport() {
+    let (mut handle, mut processor) = Engine::<128>::new().split(8);
+    handle.update_with_code("o: constsig 1").unwrap();
+    handle.pause().unwrap();
+    assert!(processor.next_block(&[])[0].iter().all(|s| *s == 0.));
+    handle.play().unwrap();
+    assert!(processor.next_block(&[])[0].iter().all(|s| *s == 1.));
+}
@@ -87,6 +87,8 @@ pub fn get_ast(code: &str) -> Result<nodes::Ast<'_>, Box<Error<Rule>>> {
                     Rule::adsr => { Component::Adsr(nodes::Adsr::parse(node)?) },
                     Rule::plate => { Component::Plate(nodes::Plate::parse(node)?) },
                     Rule::get => { Component::Get(nodes::Get::parse(node)?) },
+                    Rule::fb => { Component::Fb(nodes::Fb::parse(node)?) },
+                    Rule::out => { Component::Out(nodes::Out::parse(node)?) },
                     Rule::noise => { Component::Noise(nodes::Noise::parse(node)?) },
                     Rule::meta => { Component::Meta(nodes::Meta::parse(node)?) },
                     Rule::expr => { Component::Expr(nodes::Expr::parse(node)?) },
@@ -94,6 +96,7 @@ pub fn get_ast(code: &str) -> Result<nodes::Ast<'_>, Box<Error<Rule>>> {
                     Rule::arrange => { Component::Arrange(nodes::Arrange::parse(node)?) },
                     Rule::msgsynth => { Component::MsgSynth(nodes::MsgSynth::parse(node)?) },
                     Rule::pattern_synth => { Component::PatternSynth(nodes::PatternSynth::parse(node)?) },
+                    Rule::custom => { Component::Custom(nodes::Custom::parse(node)?) },
                 );
 
                 Ok(component)
@@ -90,10 +90,13 @@ pub enum Component<'ast> {
     EnvPerc(EnvPerc),
     Adsr(Adsr),
     Get(Get<'ast>),
+    Fb(Fb<'ast>),
+    Out(Out),
     Noise(Noise),
     Meta(Meta<'ast>),
     Expr(Expr<'ast>),
     Eval(Eval<'ast>),
+    Custom(Custom<'ast>),
 }
 
 impl<'ast> Component<'ast> {
@@ -153,7 +156,8 @@ impl<'ast> Component<'ast> {
                 delay: NumberOrRef::Ref(r),
                 gain: _,
             })
-            | Self::Get(Get { reference: r }) => vec![r],
+            | Self::Get(Get { reference: r })
+            | Self::Fb(Fb { reference: r }) => vec![r],
 
             Self::Seq(Seq { events }) => events
                 .iter()
@@ -170,6 +174,13 @@ impl<'ast> Component<'ast> {
                 })
                 .collect(),
             Self::Mix(Mix { nodes }) => nodes.clone(),
+            Self::Custom(Custom { args, .. }) => args
+                .iter()
+                .flat_map(|arg| match arg {
+                    Argument::Ref(r) => Some(*r),
+                    _ => None,
+                })
+                .collect(),
             Self::Balance(Balance { left, right }) => vec![left, right],
 
             // mmm I don't like using wildcard matches but it's definitely the most convenient in
@@ -177,6 +188,57 @@ impl<'ast> Component<'ast> {
             _ => vec![],
         }
     }
+
+    /// The keyword this component is written with. Aliases like `sig` give the main name.
+    pub fn name(&self) -> &'ast str {
+        match self {
+            Self::Points(_) => "points",
+            Self::Delayn(_) => "delayn",
+            Self::Delayms(_) => "delayms",
+            Self::Imp(_) => "imp",
+            Self::Tri(_) => "tri",
+            Self::Squ(_) => "squ",
+            Self::Saw(_) => "saw",
+            Self::Onepole(_) => "onepole",
+            Self::Sin(_) => "sin",
+            Self::Mul(_) => "mul",
+            Self::Add(_) => "add",
+            Self::Pan(_) => "pan",
+            Self::Seq(_) => "seq",
+            Self::Choose(_) => "choose",
+            Self::Arrange(_) => "arrange",
+            Self::Mix(_) => "mix",
+            Self::Sp(_) => "sp",
+            Self::Speed(_) => "speed",
+            Self::ConstSig(_) => "constsig",
+            Self::Adc(_) => "adc",
+            Self::Bd(_) => "bd",
+            Self::Sn(_) => "sn",
+            Self::Hh(_) => "hh",
+            Self::SawSynth(_) => "sawsynth",
+            Self::SquSynth(_) => "squsynth",
+            Self::TriSynth(_) => "trisynth",
+            Self::MsgSynth(_) => "msgsynth",
+            Self::PatternSynth(_) => "psynth",
+            Self::Lpf(_) => "lpf",
+            Self::PSampler(_) => "psampler",
+            Self::Balance(_) => "balance",
+            Self::Rhpf(_) => "rhpf",
+            Self::ApfmsGain(_) => "apfmsgain",
+            Self::Reverb(_) => "reverb",
+            Self::Plate(_) => "plate",
+            Self::EnvPerc(_) => "envperc",
+            Self::Adsr(_) => "adsr",
+            Self::Get(_) => "get",
+            Self::Fb(_) => "fb",
+            Self::Out(_) => "out",
+            Self::Noise(_) => "noise",
+            Self::Meta(_) => "meta",
+            Self::Expr(_) => "expr",
+            Self::Eval(_) => "eval",
+            Self::Custom(custom) => custom.name,
+        }
+    }
 }
 
 #[derive(PartialEq, Debug, Clone, PartialOrd)]
@@ -420,6 +482,27 @@ impl<'ast> Node<'ast> for Get<'ast> {
     }
 }
 
+/// Reads a chain one block late, which breaks the loop when chains read each other.
+#[derive(PartialEq, Debug)]
+pub struct Fb<'ast> {
+    pub reference: &'ast str,
+}
+
+impl<'ast> Node<'ast> for Fb<'ast> {
+    #[cfg_attr(test, trace::trace(prefix_enter = "[+ Fb]"))]
+    fn parse_from_iter(
+        pairs: &mut Pairs<'ast, Rule>,
+        span: Span<'ast>,
+    ) -> Result<Self, Box<Error<Rule>>> {
+        pairs
+            .next()
+            .ok_or_else(|| span.as_end_span().to_err_with_positives([Rule::reference]))
+            .map(|p| Self {
+                reference: p.as_str(),
+            })
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub struct Noise {
     pub seed: usize,
@@ -580,6 +663,25 @@ impl Node<'_> for Choose {
     }
 }
 
+#[derive(PartialEq, Debug)]
+pub struct Out {
+    pub channels: Vec<usize>,
+}
+
+impl Node<'_> for Out {
+    #[cfg_attr(test, trace::trace(prefix_enter = "[+ Out]"))]
+    fn parse_from_iter(
+        pairs: &mut Pairs<'_, Rule>,
+        _s: Span<'_>,
+    ) -> Result<Self, Box<Error<Rule>>> {
+        Ok(Self {
+            channels: pairs
+                .map(|n| n.try_to_parse())
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub struct Arrange<'ast> {
     pub events: Vec<NumberOrRef<&'ast str>>,
@@ -916,6 +1018,52 @@ impl<'ast> Node<'ast> for PSampler<'ast> {
     }
 }
 
+/// A node that isn't built in, like `rumble 40 \low ~throttle`.
+#[derive(PartialEq, Debug)]
+pub struct Custom<'ast> {
+    pub name: &'ast str,
+    pub args: Vec<Argument<'ast>>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Argument<'ast> {
+    Number(f32),
+    Symbol(&'ast str),
+    Ref(&'ast str),
+}
+
+impl<'ast> Node<'ast> for Custom<'ast> {
+    #[cfg_attr(test, trace::trace(prefix_enter = "[+ Custom]"))]
+    fn parse_from_iter(
+        pairs: &mut Pairs<'ast, Rule>,
+        span: Span<'ast>,
+    ) -> Result<Self, Box<Error<Rule>>> {
+        let name = pairs
+            .next()
+            .ok_or_else(|| {
+                span.as_end_span()
+                    .to_err_with_positives([Rule::custom_name])
+            })?
+            .as_str();
+        let args = pairs
+            .map(|arg| {
+                match_or_return_err!(arg,
+                    Rule::number => {
+                        arg.try_to_parse().map(Argument::Number)
+                    },
+                    Rule::symbol => {
+                        Ok(Argument::Symbol(arg.as_str()))
+                    },
+                    Rule::reference => {
+                        Ok(Argument::Ref(arg.as_str()))
+                    },
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { name, args })
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub struct Balance<'ast> {
     pub left: &'ast str,
@@ -383,3 +383,83 @@ fn balance() {
         )])
     );
 }
+
+#[test]
+fn fb() {
+    assert_eq!(
+        get_ast("~mod: fb ~a >> mul 100"),
+        ast_from_nodes([(
+            "~mod",
+            vec![
+                Component::Fb(Fb { reference: "~a" }),
+                Component::Mul(Mul {
+                    param: NumberOrRef::Number(100.)
+                }),
+            ]
+        )])
+    );
+    assert!(get_ast("o: fb 0.5").is_err());
+}
+
+#[test]
+fn out() {
+    assert_eq!(
+        get_ast("o: sin 440 >> out 3 4"),
+        ast_from_nodes([(
+            "o",
+            vec![
+                Component::Sin(Sin {
+                    param: NumberOrRef::Number(440.)
+                }),
+                Component::Out(Out {
+                    channels: vec![3, 4]
+                }),
+            ]
+        )])
+    );
+    assert!(get_ast("o: sin 440 >> out 0.5").is_err());
+    assert!(get_ast("o: sin 440 >> out ~a").is_err());
+}
+
+#[test]
+fn component_names() {
+    let ast = get_ast(r"o: sig 1 >> apfgain 10 0.5 >> sampler \808").unwrap();
+    let names: Vec<_> = ast.nodes["o"].iter().map(Component::name).collect();
+    assert_eq!(names, ["constsig", "apfmsgain", "sp"]);
+}
+
+#[test]
+fn custom() {
+    assert_eq!(
+        get_ast(r"o: rumble 40 \low ~throttle >> engine_hum 0.5"),
+        ast_from_nodes([(
+            "o",
+            vec![
+                Component::Custom(Custom {
+                    name: "rumble",
+                    args: vec![
+                        Argument::Number(40.),
+                        Argument::Symbol(r"\low"),
+                        Argument::Ref("~throttle"),
+                    ]
+                }),
+                Component::Custom(Custom {
+                    name: "engine_hum",
+                    args: vec![Argument::Number(0.5)]
+                }),
+            ]
+        )])
+    );
+
+    // without arguments it's a reference, and built-in nodes keep their own syntax
+    assert_eq!(
+        get_ast("o: engine_hum"),
+        ast_from_nodes([(
+            "o",
+            vec![Component::Get(Get {
+                reference: "engine_hum"
+            })]
+        )])
+    );
+    assert!(get_ast(r"o: sin \low").is_err());
+}
@@ -1,5 +1,8 @@
 pub use crate::{
-    buffer::Buffer, node::Node, BoxedNodeSend, Message, NodeData, Pass, Processor, Sum2,
+    buffer::Buffer,
+    effect::{Master, MasterConfig},
+    node::Node,
+    BoxedNodeSend, Message, NodeData, Pass, Processor, Smoothing,
 };
 use hashbrown::HashMap;
 use petgraph::{graph::NodeIndex, prelude::EdgeIndex};
@@ -11,6 +14,8 @@ pub struct AudioContextBuilder<const N: usize> {
     // stablegraph: bool,
     max_nodes: usize,
     max_edges: usize,
+    smoothing: Smoothing,
+    master: MasterConfig,
 }
 
 impl<const N: usize> Default for AudioContextBuilder<N> {
@@ -27,6 +32,8 @@ impl<const N: usize> AudioContextBuilder<N> {
             // stablegraph: false,
             max_nodes: 1024,
             max_edges: 1024,
+            smoothing: Smoothing::Off,
+            master: MasterConfig::default(),
         }
     }
 
@@ -46,12 +53,24 @@ impl<const N: usize> AudioContextBuilder<N> {
         Self { max_edges, ..self }
     }
 
+    /// See [`AudioContext::set_smoothing`].
+    pub fn smoothing(self, smoothing: Smoothing) -> Self {
+        Self { smoothing, ..self }
+    }
+
+    /// See [`AudioContext::set_master`].
+    pub fn master(self, master: MasterConfig) -> Self {
+        Self { master, ..self }
+    }
+
     pub fn build(self) -> AudioContext<N> {
         AudioContext::new(AudioContextConfig {
             sr: self.sr,
             channels: self.channels,
             max_nodes: self.max_nodes,
             max_edges: self.max_edges,
+            smoothing: self.smoothing,
+            master: self.master,
         })
     }
 }
@@ -63,6 +82,8 @@ pub struct AudioContextConfig {
     // pub stablegraph: bool,
     pub max_nodes: usize,
     pub max_edges: usize,
+    pub smoothing: Smoothing,
+    pub master: MasterConfig,
 }
 
 impl std::default::Default for AudioContextConfig {
@@ -72,6 +93,8 @@ impl std::default::Default for AudioContextConfig {
             channels: 2,
             max_nodes: 1024,
             max_edges: 1024,
+            smoothing: Smoothing::Off,
+            master: MasterConfig::default(),
         }
     }
 }
@@ -104,7 +127,7 @@ impl<const N: usize> AudioContext<N> {
         let mut graph = GlicolGraph::<N>::with_capacity(config.max_nodes, config.max_edges);
         let destination = graph.add_node(NodeData::multi_chan_node(
             config.channels,
-            BoxedNodeSend::<N>::new(Sum2),
+            BoxedNodeSend::<N>::new(Master::new(config.master, config.channels, config.sr)),
         ));
         let input = graph.add_node(NodeData::multi_chan_node(
             config.channels,
@@ -123,9 +146,10 @@ impl<const N: usize> AudioContext<N> {
     pub fn reset(&mut self) {
         // self.graph.clear_edges();
         self.graph.clear();
+        let master = Master::new(self.config.master, self.config.channels, self.config.sr);
         self.destination = self.graph.add_node(NodeData::multi_chan_node(
             self.config.channels,
-            BoxedNodeSend::<N>::new(Sum2),
+            BoxedNodeSend::<N>::new(master),
         ));
         self.input = self.graph.add_node(NodeData::multi_chan_node(
             self.config.channels,
@@ -151,30 +175,36 @@ impl<const N: usize> AudioContext<N> {
     where
         T: Node<N> + Send + 'static,
     {
-        self.graph.add_node(
+        let index = self.graph.add_node(
             // channel?
             NodeData::new1(BoxedNodeSend::<N>::new(node)),
-        )
+        );
+        self.smooth_node(index);
+        index
     }
 
     pub fn add_stereo_node<T>(&mut self, node: T) -> NodeIndex
     where
         T: Node<N> + Send + 'static,
     {
-        self.graph.add_node(
+        let index = self.graph.add_node(
             // channel?
             NodeData::new2(BoxedNodeSend::<N>::new(node)),
-        )
+        );
+        self.smooth_node(index);
+        index
     }
 
     pub fn add_multi_chan_node<T>(&mut self, chan: usize, node: T) -> NodeIndex
     where
         T: Node<N> + Send + 'static,
     {
-        self.graph.add_node(
+        let index = self.graph.add_node(
             // channel?
             NodeData::multi_chan_node(chan, BoxedNodeSend::<N>::new(node)),
-        )
+        );
+        self.smooth_node(index);
+        index
     }
 
     pub fn connect(&mut self, from: NodeIndex, to: NodeIndex) -> EdgeIndex {
@@ -212,6 +242,9 @@ impl<const N: usize> AudioContext<N> {
             .into_iter()
             .map(|node| self.graph.add_node(node))
             .collect::<Vec<_>>();
+        for index in &indices {
+            self.smooth_node(*index);
+        }
 
         let v = indices
             .windows(2)
@@ -235,6 +268,9 @@ impl<const N: usize> AudioContext<N> {
             .into_iter()
             .map(|node| self.graph.add_node(node))
             .collect::<Vec<_>>();
+        for index in &v {
+            self.smooth_node(*index);
+        }
 
         let j = v
             .windows(2)
@@ -253,9 +289,61 @@ impl<const N: usize> AudioContext<N> {
         self.graph[index].node.send_msg(msg);
     }
 
+    /// Changes the sample rate of every node. Nodes with delay lines reallocate them.
+    pub fn set_sr(&mut self, sr: usize) {
+        self.config.sr = sr;
+        self.send_msg_to_all(Message::SetSampleRate(sr));
+    }
+
+    pub fn sr(&self) -> usize {
+        self.config.sr
+    }
+
+    /// Configures the master stage at the destination. It is kept across [`AudioContext::reset`].
+    pub fn set_master(&mut self, master: MasterConfig) {
+        self.config.master = master;
+        self.graph[self.destination]
+            .node
+            .send_msg(Message::SetMaster(master));
+    }
+
+    pub fn master(&self) -> MasterConfig {
+        self.config.master
+    }
+
     pub fn send_msg_to_all(&mut self, msg: Message) {
         for nodedata in self.graph.node_weights_mut() {
             nodedata.node.send_msg(msg.clone());
         }
     }
+
+    /// Sets how every smoothed parameter of every node, including the ones added later,
+    /// moves to new values. Single parameters can be changed with `Message::SetSmoothing`.
+    pub fn set_smoothing(&mut self, smoothing: Smoothing) {
+        self.config.smoothing = smoothing;
+        for nodedata in self.graph.node_weights_mut() {
+            send_smoothing(&mut nodedata.node, smoothing);
+        }
+    }
+
+    pub fn smoothing(&self) -> Smoothing {
+        self.config.smoothing
+    }
+
+    /// Gives the node at `index` the smoothing of the context. Done by the methods that add
+    /// nodes, call it for nodes added to `graph` directly.
+    pub fn smooth_node(&mut self, index: NodeIndex) {
+        if self.config.smoothing != Smoothing::Off {
+            send_smoothing(&mut self.graph[index].node, self.config.smoothing);
+        }
+    }
+}
+
+fn send_smoothing<const N: usize>(node: &mut BoxedNodeSend<N>, smoothing: Smoothing) {
+    for i in 0..node.params().len() {
+        let param = node.params()[i];
+        if param.smoothed {
+            node.send_msg(Message::SetSmoothing(param.index, smoothing));
+        }
+    }
 }
@@ -36,12 +36,18 @@ mod node;
 pub use node::{
     compound, delay, effect, envelope, filter, operator, oscillator, sequencer, signal, synth,
 };
-pub use node::{Input, Node};
+pub use node::{Feedback, Input, Node, ParamInfo, ParamKind, Unit};
 // pub use node::*; // TODO: Do not expose every struct here
 
 mod buffer;
 pub use buffer::Buffer;
 
+mod smooth;
+pub use smooth::{Smoothed, Smoothing};
+
+mod sample;
+pub use sample::{Sample, SampleBank};
+
 #[cfg(feature = "node-sampling")]
 pub use node::sampling;
 
@@ -64,12 +70,8 @@ pub enum Message {
     SetToNumber(u8, f32),
     SetToNumberList(u8, Vec<f32>),
     SetToSymbol(u8, String),
-    SetToSamples(u8, (&'static [f32], usize, usize)),
-    SetSamplePattern(
-        Vec<(String, f32)>,
-        f32,
-        HashMap<String, (&'static [f32], usize, usize)>,
-    ),
+    SetToSamples(u8, Sample),
+    SetSamplePattern(Vec<(String, f32)>, f32, SampleBank),
     SetPattern(Vec<(f32, f32)>, f32),
     SetToSeq(u8, Vec<(f32, UsizeOrRef<String>)>),
     SetRefOrder(HashMap<String, usize>),
@@ -82,6 +84,21 @@ pub enum Message {
     ResetOrder,
     SetParam(u8, GlicolPara<String>),
     SetToBool(u8, bool),
+    // the transport position in samples, for nodes that follow the song time
+    SetPosition(usize),
+    // a tempo change and where the transport is at the new tempo, in samples; nodes on the
+    // song grid move there without restarting, so they keep their place in the bar
+    SetTempo(f32, usize),
+    // how the numeric parameter at this index moves to new values
+    SetSmoothing(u8, Smoothing),
+    // for the master stage at the destination
+    SetMaster(effect::MasterConfig),
+    // restarts a random node from this seed
+    SetSeed(u64),
+    // plays a note of this midi pitch, for nodes that play notes
+    Note(f32),
+    // plays a sample once at this rate, for samplers
+    Trigger(f32),
 }
 
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
@@ -10,7 +10,7 @@ use crate::{
     AudioContext,
     Pass,
 };
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use hashbrown::HashMap;
 
 use petgraph::graph::NodeIndex;
@@ -24,6 +24,11 @@ pub struct SawSynth<const N: usize> {
 }
 
 impl<const N: usize> SawSynth<N> {
+    pub const PARAMS: &[ParamInfo] = &[
+        ParamInfo::number("attack", 0, 0.01).unit(Unit::Seconds),
+        ParamInfo::number("decay", 1, 0.1).unit(Unit::Seconds),
+    ];
+
     pub fn new(attack: f32, decay: f32) -> Self {
         let mut context = crate::AudioContextBuilder::<N>::new().channels(2).build();
         let input = context.add_mono_node(Pass {});
@@ -46,6 +51,12 @@ impl<const N: usize> SawSynth<N> {
             input_order: vec![],
         }
     }
+
+    /// Runs the inner nodes at `sr`.
+    pub fn sr(mut self, sr: usize) -> Self {
+        self.context.set_sr(sr);
+        self
+    }
 }
 
 impl<const N: usize> Node<N> for SawSynth<N> {
@@ -70,9 +81,14 @@ impl<const N: usize> Node<N> for SawSynth<N> {
                     .send_msg(Message::SetToNumber(1, value)),
                 _ => {}
             },
+            Message::SetSampleRate(sr) => self.context.set_sr(sr),
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
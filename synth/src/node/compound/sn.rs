@@ -13,7 +13,7 @@ use crate::{
     AudioContext,
     Pass,
 };
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use hashbrown::HashMap;
 
 use petgraph::graph::NodeIndex;
@@ -27,9 +27,19 @@ pub struct Sn<const N: usize> {
 }
 
 impl<const N: usize> Sn<N> {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("decay", 0, 0.3)
+        .unit(Unit::Seconds)
+        .with_ref()];
+
     pub fn new(decay: f32) -> Self {
         Self::from(decay)
     }
+
+    /// Runs the inner nodes at `sr`.
+    pub fn sr(mut self, sr: usize) -> Self {
+        self.context.set_sr(sr);
+        self
+    }
 }
 
 impl<const N: usize> From<f32> for Sn<N> {
@@ -79,9 +89,15 @@ impl<const N: usize> Node<N> for Sn<N> {
             Message::SetToNumber(0, value) => self.context.graph[self.context.tags["d"]]
                 .node
                 .send_msg(Message::SetToNumber(1, value)),
+            Message::SetSampleRate(sr) => self.context.set_sr(sr),
+            Message::SetSeed(_) => self.context.send_msg_to_all(info),
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -3,7 +3,7 @@
 // ~pitch: ~trigger >> mul 261.626;
 // ~env: ~trigger >> envperc #attack #decay;
 use crate::{envelope::EnvPerc, operator::Mul, oscillator::SquOsc, AudioContext, Pass};
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use hashbrown::HashMap;
 
 use petgraph::graph::NodeIndex;
@@ -17,6 +17,11 @@ pub struct SquSynth<const N: usize> {
 }
 
 impl<const N: usize> SquSynth<N> {
+    pub const PARAMS: &[ParamInfo] = &[
+        ParamInfo::number("attack", 0, 0.01).unit(Unit::Seconds),
+        ParamInfo::number("decay", 1, 0.1).unit(Unit::Seconds),
+    ];
+
     pub fn new(attack: f32, decay: f32) -> Self {
         let mut context = crate::AudioContextBuilder::<N>::new().channels(2).build();
         let input = context.add_mono_node(Pass {});
@@ -39,6 +44,12 @@ impl<const N: usize> SquSynth<N> {
             input_order: vec![],
         }
     }
+
+    /// Runs the inner nodes at `sr`.
+    pub fn sr(mut self, sr: usize) -> Self {
+        self.context.set_sr(sr);
+        self
+    }
 }
 
 impl<const N: usize> Node<N> for SquSynth<N> {
@@ -58,9 +69,14 @@ impl<const N: usize> Node<N> for SquSynth<N> {
                 .node
                 .send_msg(Message::SetToNumber(pos, value)),
 
+            Message::SetSampleRate(sr) => self.context.set_sr(sr),
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use dasp_ring_buffer as ring_buffer;
 type Fixed = ring_buffer::Fixed<Vec<f32>>;
 use hashbrown::HashMap;
@@ -7,6 +7,7 @@ pub struct DelayMs {
     buf: Vec<Fixed>,
     sr: usize,
     input_order: Vec<usize>,
+    delay: f32,
     delay_n: usize,
 }
 
@@ -17,9 +18,14 @@ impl Default for DelayMs {
 }
 
 impl DelayMs {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("delay", 0, 0.0)
+        .unit(Unit::Milliseconds)
+        .with_ref()];
+
     pub fn new() -> Self {
         Self {
             buf: vec![],
+            delay: 0.,
             delay_n: 1,
             sr: 44100,
             input_order: vec![],
@@ -32,6 +38,7 @@ impl DelayMs {
 
         Self {
             buf,
+            delay,
             delay_n,
             ..self
         }
@@ -40,6 +47,19 @@ impl DelayMs {
     pub fn sr(self, sr: usize) -> Self {
         Self { sr, ..self }
     }
+
+    fn set_delay(&mut self, delay: f32) {
+        let delay_n = (delay / 1000. * self.sr as f32) as usize;
+        self.delay = delay;
+        self.delay_n = delay_n;
+
+        if delay_n == 0 {
+            self.buf.clear();
+        } else {
+            let chan = self.buf.len();
+            self.buf = vec![Fixed::from(vec![0.0; delay_n]); chan];
+        };
+    }
 }
 
 impl<const N: usize> Node<N> for DelayMs {
@@ -106,16 +126,10 @@ impl<const N: usize> Node<N> for DelayMs {
 
     fn send_msg(&mut self, info: Message) {
         match info {
-            Message::SetToNumber(0, value) => {
-                let delay_n = (value / 1000. * self.sr as f32) as usize;
-                self.delay_n = delay_n;
-
-                if delay_n == 0 {
-                    self.buf.clear();
-                } else {
-                    let chan = self.buf.len();
-                    self.buf = vec![Fixed::from(vec![0.0; delay_n]); chan];
-                };
+            Message::SetToNumber(0, value) => self.set_delay(value),
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.set_delay(self.delay);
             }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
@@ -125,4 +139,8 @@ impl<const N: usize> Node<N> for DelayMs {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use dasp_ring_buffer as ring_buffer;
 use hashbrown::HashMap;
 type Fixed = ring_buffer::Fixed<Vec<f32>>;
@@ -11,6 +11,10 @@ pub struct DelayN {
 }
 
 impl DelayN {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("samples", 0, 0.0)
+        .unit(Unit::Samples)
+        .with_ref()];
+
     pub fn new(n: usize) -> Self {
         let delay_n = n;
         let init_n = match n {
@@ -75,4 +79,8 @@ impl<const N: usize> Node<N> for DelayN {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
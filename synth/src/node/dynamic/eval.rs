@@ -1,4 +1,4 @@
-use crate::{BoxedNodeSend, Buffer, Input, Message, Node, NodeData};
+use crate::{BoxedNodeSend, Buffer, Input, Message, Node, NodeData, ParamInfo};
 use hashbrown::HashMap;
 // use evalexpr::*;
 use fasteval::Compiler;
@@ -27,6 +27,8 @@ impl<const N: usize> Default for Eval<N> {
 }
 
 impl<const N: usize> Eval<N> {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::symbol("code", 0)];
+
     pub fn new() -> Self {
         let parser = fasteval::Parser::new();
         // let mut slab = fasteval::Slab::new();
@@ -128,6 +130,10 @@ impl<const N: usize> Node<N> for Eval<N> {
                 // self.code = s
                 // self.precompiled = build_operator_tree(&s).unwrap()
             }
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.map.insert("sr".to_string(), sr as f64);
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -137,6 +143,10 @@ impl<const N: usize> Node<N> for Eval<N> {
         }
     }
 
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
+
     fn to_boxed_nodedata(self, channels: usize) -> NodeData<BoxedNodeSend<N>, N> {
         // self.scope.push("sr", self.sr as f32);
         NodeData::multi_chan_node(channels, BoxedNodeSend::<N>::new(self))
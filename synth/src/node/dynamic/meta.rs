@@ -1,4 +1,4 @@
-use crate::{BoxedNodeSend, Buffer, Input, Message, Node, NodeData};
+use crate::{BoxedNodeSend, Buffer, Input, Message, Node, NodeData, ParamInfo};
 use hashbrown::HashMap;
 use rhai::{Array, Dynamic, Engine, OptimizationLevel, Scope, AST};
 
@@ -19,6 +19,8 @@ impl<const N: usize> Default for Meta<N> {
 }
 
 impl<const N: usize> Meta<N> {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::symbol("code", 0)];
+
     pub fn new() -> Self {
         let phase: usize = 0;
         let mut scope = Scope::new();
@@ -132,6 +134,7 @@ impl<const N: usize> Node<N> for Meta<N> {
                     self.ast = a;
                 }
             }
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -141,6 +144,10 @@ impl<const N: usize> Node<N> for Meta<N> {
         }
     }
 
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
+
     fn to_boxed_nodedata(mut self, channels: usize) -> NodeData<BoxedNodeSend<N>, N> {
         self.scope.push("sr", self.sr as f32);
         NodeData::multi_chan_node(channels, BoxedNodeSend::<N>::new(self))
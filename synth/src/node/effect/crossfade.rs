@@ -0,0 +1,54 @@
+use crate::{node::input_channel, Buffer, Input, Message, Node};
+use hashbrown::HashMap;
+
+/// Fades linearly from one input to another over `len` samples.
+///
+/// The input connected with `IndexOrder(0, ..)` fades out and the one connected with
+/// `IndexOrder(1, ..)` fades in. Either can be missing, to fade a single input in or out.
+#[derive(Debug, Clone)]
+pub struct CrossFade {
+    from: Option<usize>,
+    to: Option<usize>,
+    len: usize,
+    pos: usize,
+}
+
+impl CrossFade {
+    pub fn new(len: usize) -> Self {
+        Self {
+            from: None,
+            to: None,
+            len: len.max(1),
+            pos: 0,
+        }
+    }
+}
+
+impl<const N: usize> Node<N> for CrossFade {
+    fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
+        let from = self.from.and_then(|id| inputs.get(&id));
+        let to = self.to.and_then(|id| inputs.get(&id));
+        for (c, out) in output.iter_mut().enumerate() {
+            let from = from.and_then(|input| input_channel(input.buffers(), c));
+            let to = to.and_then(|input| input_channel(input.buffers(), c));
+            for (i, sample) in out.iter_mut().enumerate() {
+                let gain = ((self.pos + i) as f32 / self.len as f32).min(1.0);
+                *sample = from.map_or(0.0, |buf| buf[i] * (1.0 - gain))
+                    + to.map_or(0.0, |buf| buf[i] * gain);
+            }
+        }
+        self.pos += N;
+    }
+
+    fn send_msg(&mut self, info: Message) {
+        match info {
+            Message::IndexOrder(0, index) => self.from = Some(index),
+            Message::IndexOrder(1, index) => self.to = Some(index),
+            Message::ResetOrder => {
+                self.from = None;
+                self.to = None;
+            }
+            _ => {}
+        }
+    }
+}
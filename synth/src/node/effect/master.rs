@@ -0,0 +1,285 @@
+use crate::{node::InputOrder, Buffer, Input, Message, Node, ParamInfo};
+use hashbrown::HashMap;
+
+// the delay lines are sized for this, so changing the lookahead never allocates
+const MAX_LOOKAHEAD_MS: f32 = 20.;
+
+/// The settings of the [`Master`] stage. Everything is off by default, leaving the master
+/// a plain sum of its inputs times the gain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasterConfig {
+    /// Removes DC offset with a 10 Hz high-pass.
+    pub dc_block: bool,
+    /// Keeps true peaks under `ceiling`, delaying the output by `lookahead`.
+    pub limiter: bool,
+    /// In dBFS.
+    pub ceiling: f32,
+    /// In milliseconds, up to 20.
+    pub lookahead: f32,
+    /// In milliseconds.
+    pub release: f32,
+    /// Silences any block with a NaN or infinite sample and clears the master's state.
+    pub mute_nonfinite: bool,
+}
+
+impl Default for MasterConfig {
+    fn default() -> Self {
+        Self {
+            dc_block: false,
+            limiter: false,
+            ceiling: -1.,
+            lookahead: 5.,
+            release: 100.,
+            mute_nonfinite: false,
+        }
+    }
+}
+
+impl MasterConfig {
+    /// DC blocking, the limiter at -1 dBFS and the safety mute.
+    pub fn safe() -> Self {
+        Self {
+            dc_block: true,
+            limiter: true,
+            mute_nonfinite: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn dc_block(self, dc_block: bool) -> Self {
+        Self { dc_block, ..self }
+    }
+
+    pub fn limiter(self, limiter: bool) -> Self {
+        Self { limiter, ..self }
+    }
+
+    pub fn ceiling(self, ceiling: f32) -> Self {
+        Self { ceiling, ..self }
+    }
+
+    pub fn lookahead(self, lookahead: f32) -> Self {
+        Self { lookahead, ..self }
+    }
+
+    pub fn release(self, release: f32) -> Self {
+        Self { release, ..self }
+    }
+
+    pub fn mute_nonfinite(self, mute_nonfinite: bool) -> Self {
+        Self {
+            mute_nonfinite,
+            ..self
+        }
+    }
+}
+
+/// The destination of an `AudioContext`: sums its inputs channel by channel, with mono inputs
+/// on the first two channels, then applies the gain and whatever [`MasterConfig`] turns on.
+#[derive(Debug, Clone)]
+pub struct Master {
+    config: MasterConfig,
+    gain: f32,
+    sr: usize,
+    // per channel: the lookahead delay line, the last four samples going in,
+    // and the last input and output of the dc blocker
+    delay: Vec<Vec<f32>>,
+    history: Vec<[f32; 4]>,
+    dc: Vec<(f32, f32)>,
+    write: usize,
+    len: usize,
+    order: InputOrder,
+    ceiling: f32,
+    dc_coef: f32,
+    release_coef: f32,
+    // the limiter gain, the gain it is heading to and how fast,
+    // and how long until the loudest peak seen has left the delay line
+    env: f32,
+    target: f32,
+    step: f32,
+    hold: usize,
+}
+
+impl Master {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("gain", 0, 1.0)];
+
+    pub fn new(config: MasterConfig, channels: usize, sr: usize) -> Self {
+        let mut master = Self {
+            config,
+            gain: 1.,
+            sr,
+            delay: vec![vec![]; channels],
+            history: vec![[0.; 4]; channels],
+            dc: vec![(0., 0.); channels],
+            write: 0,
+            len: 1,
+            order: InputOrder::default(),
+            ceiling: 1.,
+            dc_coef: 0.,
+            release_coef: 0.,
+            env: 1.,
+            target: 1.,
+            step: 0.,
+            hold: 0,
+        };
+        master.set_sr(sr);
+        master
+    }
+
+    pub fn gain(self, gain: f32) -> Self {
+        Self { gain, ..self }
+    }
+
+    fn set_sr(&mut self, sr: usize) {
+        self.sr = sr;
+        let size = (MAX_LOOKAHEAD_MS * sr as f32 / 1000.) as usize + 3;
+        for line in &mut self.delay {
+            *line = vec![0.; size];
+        }
+        self.write = 0;
+        self.set_config(self.config);
+    }
+
+    fn set_config(&mut self, config: MasterConfig) {
+        if config.limiter && !self.config.limiter {
+            self.clear();
+        }
+        self.config = config;
+        let samples = |ms: f32| ms.max(0.) * self.sr as f32 / 1000.;
+        let lookahead = samples(config.lookahead.min(MAX_LOOKAHEAD_MS));
+        self.len = (lookahead as usize).max(1);
+        self.ceiling = 10f32.powf(config.ceiling / 20.);
+        self.dc_coef = 1. - std::f32::consts::TAU * 10. / self.sr as f32;
+        self.release_coef = (-1. / samples(config.release)).exp();
+    }
+
+    fn clear(&mut self) {
+        for line in &mut self.delay {
+            line.fill(0.);
+        }
+        self.history.fill([0.; 4]);
+        self.dc.fill((0., 0.));
+        self.env = 1.;
+        self.target = 1.;
+        self.step = 0.;
+        self.hold = 0;
+    }
+
+    fn is_bypassed(&self) -> bool {
+        let config = &self.config;
+        self.gain == 1. && !config.dc_block && !config.limiter && !config.mute_nonfinite
+    }
+
+    // moves the limiter gain one sample on, given the true peak of the sample going in
+    fn follow_peak(&mut self, peak: f32) -> f32 {
+        if peak > self.ceiling {
+            let required = self.ceiling / peak;
+            if required < self.target {
+                self.target = required;
+                // reach it by the time the peak leaves the delay line, keeping
+                // any steeper ramp an earlier peak still needs
+                self.step = self.step.min((required - self.env) / self.len as f32);
+            }
+            self.hold = self.len + 2;
+        }
+        if self.env > self.target {
+            self.env = (self.env + self.step).max(self.target);
+        } else if self.hold == 0 {
+            self.env = 1. - (1. - self.env) * self.release_coef;
+        }
+        if self.env <= self.target {
+            self.step = 0.;
+        }
+        self.hold = self.hold.saturating_sub(1);
+        if self.hold == 0 {
+            self.target = 1.;
+        }
+        self.env
+    }
+}
+
+// the largest of the newest sample and three points interpolated between the two before
+fn true_peak([x0, x1, x2, x3]: [f32; 4]) -> f32 {
+    let catmull_rom = |t: f32| {
+        0.5 * (2. * x1
+            + (x2 - x0) * t
+            + (2. * x0 - 5. * x1 + 4. * x2 - x3) * t * t
+            + (3. * x1 - x0 - 3. * x2 + x3) * t * t * t)
+    };
+    [0.25, 0.5, 0.75]
+        .into_iter()
+        .fold(x3.abs(), |peak, t| peak.max(catmull_rom(t).abs()))
+}
+
+impl<const N: usize> Node<N> for Master {
+    fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
+        for out_buffer in output.iter_mut() {
+            out_buffer.silence();
+        }
+        let order = self.order.follow(inputs);
+        for (channel, out_buffer) in output.iter_mut().enumerate() {
+            for input in order.iter().map(|id| &inputs[id]) {
+                if let Some(in_buffer) = crate::node::input_channel(input.buffers(), channel) {
+                    dasp_slice::add_in_place(out_buffer, in_buffer);
+                }
+            }
+        }
+        if self.is_bypassed() {
+            return;
+        }
+        if self.config.mute_nonfinite
+            && output.iter().flat_map(|b| b.iter()).any(|s| !s.is_finite())
+        {
+            for out_buffer in output.iter_mut() {
+                out_buffer.silence();
+            }
+            self.clear();
+            return;
+        }
+
+        let channels = output.len().min(self.delay.len());
+        let size = self.delay.first().map_or(1, Vec::len);
+        for i in 0..N {
+            let mut peak = 0f32;
+            for (c, out_buffer) in output.iter_mut().enumerate().take(channels) {
+                let mut x = out_buffer[i] * self.gain;
+                if self.config.dc_block {
+                    let (last_in, last_out) = self.dc[c];
+                    let y = x - last_in + self.dc_coef * last_out;
+                    self.dc[c] = (x, y);
+                    x = y;
+                }
+                if self.config.limiter {
+                    let history = &mut self.history[c];
+                    history.rotate_left(1);
+                    history[3] = x;
+                    peak = peak.max(true_peak(*history));
+                    self.delay[c][self.write] = x;
+                } else {
+                    out_buffer[i] = x;
+                }
+            }
+            if self.config.limiter {
+                let env = self.follow_peak(peak);
+                let read = (self.write + size - self.len - 2) % size;
+                for (c, out_buffer) in output.iter_mut().enumerate().take(channels) {
+                    out_buffer[i] = (self.delay[c][read] * env).clamp(-self.ceiling, self.ceiling);
+                }
+                self.write = (self.write + 1) % size;
+            }
+        }
+    }
+
+    fn send_msg(&mut self, info: Message) {
+        match info {
+            Message::SetToNumber(0, gain) => self.gain = gain,
+            Message::SetMaster(config) => self.set_config(config),
+            Message::SetSampleRate(sr) => self.set_sr(sr),
+            _ => {}
+        }
+    }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
+}
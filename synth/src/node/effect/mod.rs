@@ -4,6 +4,12 @@ mod balance;
 pub use balance::*;
 mod pan;
 pub use pan::*;
+mod crossfade;
+pub use crossfade::*;
+mod master;
+pub use master::*;
+mod out;
+pub use out::*;
 
 // mod reverb; pub use reverb::*;
 // pub mod reverb; pub use reverb::*;
@@ -0,0 +1,43 @@
+use crate::{Buffer, Input, Message, Node};
+use hashbrown::HashMap;
+
+/// Sends its input to chosen output channels: the first input channel to the first of
+/// `channels`, the second to the second, and a mono input to all of them. Every other
+/// channel is silent, so the node should be as wide as the output.
+#[derive(Debug, Clone)]
+pub struct Out {
+    channels: Vec<usize>,
+}
+
+impl Out {
+    /// `channels` count from 0.
+    pub fn new(channels: Vec<usize>) -> Self {
+        Self { channels }
+    }
+}
+
+impl<const N: usize> Node<N> for Out {
+    fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
+        for out_buffer in output.iter_mut() {
+            out_buffer.silence();
+        }
+        let Some(input) = inputs.values().next() else {
+            return;
+        };
+        let in_buffers = input.buffers();
+        for (i, channel) in self.channels.iter().enumerate() {
+            let in_buffer = match in_buffers {
+                [mono] => mono,
+                _ => match in_buffers.get(i) {
+                    Some(in_buffer) => in_buffer,
+                    None => continue,
+                },
+            };
+            if let Some(out_buffer) = output.get_mut(*channel) {
+                out_buffer.copy_from_slice(in_buffer);
+            }
+        }
+    }
+
+    fn send_msg(&mut self, _info: Message) {}
+}
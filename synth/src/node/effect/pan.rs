@@ -1,19 +1,32 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Smoothed};
 use hashbrown::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct Pan {
     pan_pos: f32, // Pan position (-1.0 to 1.0)
+    pan_smooth: Smoothed,
+    sr: usize,
     input_order: Vec<usize>,
 }
 
 impl Pan {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("pan", 0, 0.0)
+        .range(-1., 1.)
+        .with_ref()
+        .smoothed()];
+
     pub fn new(pan_pos: f32) -> Self {
         Self {
             pan_pos,
+            pan_smooth: Smoothed::default(),
+            sr: 44100,
             input_order: vec![],
         }
     }
+
+    pub fn sr(self, sr: usize) -> Self {
+        Self { sr, ..self }
+    }
 }
 
 impl<const N: usize> Node<N> for Pan {
@@ -26,6 +39,7 @@ impl<const N: usize> Node<N> for Pan {
             let pan_norm = (self.pan_pos + 1.0) / 2.0; // Normalize pan position to 0.0 to 1.0
             let left_gain = (1.0 - pan_norm).sqrt(); // Left channel gain
             let right_gain = pan_norm.sqrt(); // Right channel gain
+            let settled = self.pan_smooth.is_settled(self.pan_pos);
 
             let (left, right) = output.split_at_mut(1);
             for ((left, right), sample) in left[0]
@@ -33,8 +47,14 @@ impl<const N: usize> Node<N> for Pan {
                 .zip(right[0].iter_mut())
                 .zip(input_buffers[0].iter())
             {
-                *left = left_gain * sample; // Left channel
-                *right = right_gain * sample; // Right channel
+                if settled {
+                    *left = left_gain * sample; // Left channel
+                    *right = right_gain * sample; // Right channel
+                } else {
+                    let pan_norm = (self.pan_smooth.follow(self.pan_pos) + 1.0) / 2.0;
+                    *left = (1.0 - pan_norm).sqrt() * sample;
+                    *right = pan_norm.sqrt() * sample;
+                }
             }
         } else {
             let ref_input = &inputs[&self.input_order[1]]; // can panic if there is no id
@@ -60,6 +80,13 @@ impl<const N: usize> Node<N> for Pan {
         match info {
             // Clamp pan position within valid range
             Message::SetToNumber(0, value) => self.pan_pos = value.clamp(-1.0, 1.0),
+            Message::SetSmoothing(0, smoothing) => {
+                self.pan_smooth.set_smoothing(smoothing, self.sr)
+            }
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.pan_smooth.set_sr(sr);
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -68,4 +95,8 @@ impl<const N: usize> Node<N> for Pan {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
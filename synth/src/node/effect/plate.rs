@@ -5,7 +5,7 @@ use crate::{
     node::Pass,
     operator::{Add, Mul},
     oscillator::SinOsc,
-    AudioContext, Buffer, Input, Message, Node,
+    AudioContext, Buffer, Input, Message, Node, ParamInfo,
 };
 use hashbrown::HashMap;
 use petgraph::graph::NodeIndex;
@@ -17,6 +17,8 @@ pub struct Plate<const N: usize> {
 }
 
 impl<const N: usize> Plate<N> {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("mix", 0, 0.1).range(0., 1.)];
+
     pub fn new(mix: f32) -> Self {
         let mut context = crate::AudioContextBuilder::<N>::new().channels(2).build();
 
@@ -97,7 +99,7 @@ impl<const N: usize> Plate<N> {
         context.chain(vec![fb, fb1, fb2, wet7]); // back to feedback
 
         // start to take some signal out
-        let left_subtract = context.add_mono_node(crate::node::Sum {});
+        let left_subtract = context.add_mono_node(crate::node::Sum::default());
         context.connect(bb, left_subtract);
         context.connect(db, left_subtract);
         context.connect(ea2, left_subtract);
@@ -107,14 +109,14 @@ impl<const N: usize> Plate<N> {
         let left_subtract2 = context.add_mono_node(Mul::new(-1.0));
         context.connect(left_subtract, left_subtract2);
 
-        let left = context.add_mono_node(crate::node::Sum {});
+        let left = context.add_mono_node(crate::node::Sum::default());
         context.connect(aa, left);
         context.connect(ab, left);
         context.connect(cb, left);
         context.connect(left_subtract2, left);
         let leftwet = context.add_mono_node(Mul::new(mix));
         context.tags.insert("mix1", leftwet);
-        let leftmix = context.add_mono_node(crate::node::Sum {});
+        let leftmix = context.add_mono_node(crate::node::Sum::default());
 
         // input dry * (1.-mix)
         let leftdrymix = context.add_mono_node(Mul::new(1. - mix));
@@ -122,7 +124,7 @@ impl<const N: usize> Plate<N> {
         context.chain(vec![input, leftdrymix, leftmix]);
         context.chain(vec![left, leftwet, leftmix]);
 
-        let right_subtract = context.add_mono_node(crate::node::Sum {});
+        let right_subtract = context.add_mono_node(crate::node::Sum::default());
         context.connect(eb, right_subtract);
         context.connect(ab, right_subtract);
         context.connect(ba[2], right_subtract);
@@ -130,14 +132,14 @@ impl<const N: usize> Plate<N> {
         let right_subtract2 = context.add_mono_node(Mul::new(-1.0));
         context.connect(right_subtract, right_subtract2);
 
-        let right = context.add_mono_node(crate::node::Sum {});
+        let right = context.add_mono_node(crate::node::Sum::default());
         context.connect(da2, right);
         context.connect(db, right);
         context.connect(fb, right);
         context.connect(right_subtract2, right);
         let rightwet = context.add_mono_node(Mul::new(mix));
         context.tags.insert("mix2", rightwet);
-        let rightmix = context.add_mono_node(crate::node::Sum {}); // input dry * (1.-mix)
+        let rightmix = context.add_mono_node(crate::node::Sum::default()); // input dry * (1.-mix)
 
         let rightdry = context.add_mono_node(Mul::new(1. - mix));
         context.tags.insert("mixdiff2", rightdry);
@@ -154,6 +156,12 @@ impl<const N: usize> Plate<N> {
             input_order: Vec::new(),
         }
     }
+
+    /// Runs the inner nodes at `sr`.
+    pub fn sr(mut self, sr: usize) -> Self {
+        self.context.set_sr(sr);
+        self
+    }
 }
 
 impl<const N: usize> Node<N> for Plate<N> {
@@ -184,6 +192,7 @@ impl<const N: usize> Node<N> for Plate<N> {
                     .node
                     .send_msg(Message::SetToNumber(0, 1. - value));
             }
+            Message::SetSampleRate(sr) => self.context.set_sr(sr),
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -192,4 +201,8 @@ impl<const N: usize> Node<N> for Plate<N> {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
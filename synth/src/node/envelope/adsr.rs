@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use hashbrown::HashMap;
 #[derive(Debug, Clone)]
 pub struct Adsr {
@@ -24,6 +24,13 @@ impl Default for Adsr {
 }
 
 impl Adsr {
+    pub const PARAMS: &[ParamInfo] = &[
+        ParamInfo::number("attack", 0, 0.01).unit(Unit::Seconds),
+        ParamInfo::number("decay", 1, 0.1).unit(Unit::Seconds),
+        ParamInfo::number("sustain", 2, 0.3).range(0., 1.),
+        ParamInfo::number("release", 3, 0.1).unit(Unit::Seconds),
+    ];
+
     pub fn new() -> Self {
         Self {
             attack: 0.01,
@@ -144,6 +151,7 @@ impl<const N: usize> Node<N> for Adsr {
                 3 => self.release = value,
                 _ => {}
             },
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -152,6 +160,10 @@ impl<const N: usize> Node<N> for Adsr {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
 
 // enum Phase {
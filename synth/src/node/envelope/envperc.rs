@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use hashbrown::HashMap;
 #[derive(Debug, Clone)]
 pub struct EnvPerc {
@@ -17,6 +17,11 @@ impl Default for EnvPerc {
 }
 
 impl EnvPerc {
+    pub const PARAMS: &[ParamInfo] = &[
+        ParamInfo::number("attack", 0, 0.01).unit(Unit::Seconds),
+        ParamInfo::number("decay", 1, 0.1).unit(Unit::Seconds),
+    ];
+
     pub fn new() -> Self {
         Self {
             attack: 0.01,
@@ -84,6 +89,7 @@ impl<const N: usize> Node<N> for EnvPerc {
                 1 => self.decay = value,
                 _ => {}
             },
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -92,4 +98,8 @@ impl<const N: usize> Node<N> for EnvPerc {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -1,10 +1,11 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use dasp_ring_buffer as ring_buffer;
 use hashbrown::HashMap;
 type Fixed = ring_buffer::Fixed<Vec<f32>>;
 
 pub struct AllPassFilterGain {
     gain: f32,
+    delay: f32,
     bufx: Fixed,
     bufy: Fixed,
     sr: usize,
@@ -18,9 +19,17 @@ impl Default for AllPassFilterGain {
 }
 
 impl AllPassFilterGain {
+    pub const PARAMS: &[ParamInfo] = &[
+        ParamInfo::number("delay", 0, 0.0)
+            .unit(Unit::Milliseconds)
+            .with_ref(),
+        ParamInfo::number("gain", 1, 0.5).range(0., 1.),
+    ];
+
     pub fn new() -> Self {
         Self {
             gain: 0.5,
+            delay: 0.,
             sr: 44100,
             bufx: ring_buffer::Fixed::from(vec![0.0]),
             bufy: ring_buffer::Fixed::from(vec![0.0]),
@@ -29,12 +38,9 @@ impl AllPassFilterGain {
     }
 
     pub fn delay(self, delay: f32) -> Self {
-        let size = if delay == 0.0 {
-            3. * self.sr as f32
-        } else {
-            delay / 1000. * self.sr as f32
-        } as usize;
+        let size = buffer_size(delay, self.sr);
         Self {
+            delay,
             bufx: ring_buffer::Fixed::from(vec![0.0; size]),
             bufy: ring_buffer::Fixed::from(vec![0.0; size]),
             ..self
@@ -50,6 +56,12 @@ impl AllPassFilterGain {
     }
 }
 
+// 0 makes room for a modulated delay of up to 3 seconds
+fn buffer_size(delay: f32, sr: usize) -> usize {
+    let seconds = if delay == 0.0 { 3. } else { delay / 1000. };
+    (seconds * sr as f32) as usize
+}
+
 impl<const N: usize> Node<N> for AllPassFilterGain {
     fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
         // println!("inputs[1] {:?}", inputs[1].buffers());
@@ -109,6 +121,12 @@ impl<const N: usize> Node<N> for AllPassFilterGain {
                 1 => self.gain = value,
                 _ => {}
             },
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                let size = buffer_size(self.delay, sr);
+                self.bufx = Fixed::from(vec![0.0; size]);
+                self.bufy = Fixed::from(vec![0.0; size]);
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
 
@@ -118,4 +136,8 @@ impl<const N: usize> Node<N> for AllPassFilterGain {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo};
 use hashbrown::HashMap;
 #[derive(Debug, Clone)]
 pub struct OnePole {
@@ -9,6 +9,8 @@ pub struct OnePole {
 }
 
 impl OnePole {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("rate", 0, 0.9).range(0., 1.).with_ref()];
+
     pub fn new(rate: f32) -> Self {
         Self::from(rate)
     }
@@ -73,4 +75,8 @@ impl<const N: usize> Node<N> for OnePole {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
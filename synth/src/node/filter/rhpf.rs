@@ -1,9 +1,11 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Smoothed, Unit};
 use hashbrown::HashMap;
 #[derive(Debug, Clone)]
 pub struct ResonantHighPassFilter {
     cutoff: f32,
     q: f32,
+    cutoff_smooth: Smoothed,
+    q_smooth: Smoothed,
     x0: f32,
     x1: f32,
     x2: f32,
@@ -20,10 +22,21 @@ impl Default for ResonantHighPassFilter {
 }
 
 impl ResonantHighPassFilter {
+    pub const PARAMS: &[ParamInfo] = &[
+        ParamInfo::number("cutoff", 0, 20.0)
+            .unit(Unit::Hz)
+            .range(20., 20000.)
+            .with_ref()
+            .smoothed(),
+        ParamInfo::number("q", 1, 1.0).smoothed(),
+    ];
+
     pub fn new() -> Self {
         Self {
             cutoff: 20.,
             q: 1.0,
+            cutoff_smooth: Smoothed::default(),
+            q_smooth: Smoothed::default(),
             x0: 0.,
             x1: 0.,
             x2: 0.,
@@ -46,22 +59,37 @@ impl ResonantHighPassFilter {
     }
 }
 
+fn highpass_coefficients(cutoff: f32, q: f32, sr: usize) -> [f32; 5] {
+    let theta_c = 2.0 * std::f32::consts::PI * cutoff / sr as f32;
+    let d = 1.0 / q;
+    let beta = 0.5 * (1.0 - d * theta_c.sin() / 2.0) / (1.0 + d * theta_c.sin() / 2.0);
+    let gama = (0.5 + beta) * theta_c.cos();
+    let a0 = (0.5 + beta + gama) / 2.0;
+    let a1 = -0.5 - beta - gama;
+    let a2 = (0.5 + beta + gama) / 2.0;
+    let b1 = -2.0 * gama;
+    let b2 = 2.0 * beta;
+    [a0, a1, a2, b1, b2]
+}
+
 impl<const N: usize> Node<N> for ResonantHighPassFilter {
     fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
         // println!("\n\ninputs[1] \n\n {:?}\n\n", inputs[1].buffers());
         match inputs.len() {
             1 => {
                 let main_input = inputs.values_mut().next().unwrap();
-                let theta_c = 2.0 * std::f32::consts::PI * self.cutoff / self.sr as f32;
-                let d = 1.0 / self.q;
-                let beta = 0.5 * (1.0 - d * theta_c.sin() / 2.0) / (1.0 + d * theta_c.sin() / 2.0);
-                let gama = (0.5 + beta) * theta_c.cos();
-                let a0 = (0.5 + beta + gama) / 2.0;
-                let a1 = -0.5 - beta - gama;
-                let a2 = (0.5 + beta + gama) / 2.0;
-                let b1 = -2.0 * gama;
-                let b2 = 2.0 * beta;
+                let settled =
+                    self.cutoff_smooth.is_settled(self.cutoff) && self.q_smooth.is_settled(self.q);
+                let mut coefficients = highpass_coefficients(self.cutoff, self.q, self.sr);
                 for (out, x0) in output[0].iter_mut().zip(main_input.buffers()[0].iter()) {
+                    if !settled {
+                        coefficients = highpass_coefficients(
+                            self.cutoff_smooth.follow(self.cutoff),
+                            self.q_smooth.follow(self.q),
+                            self.sr,
+                        );
+                    }
+                    let [a0, a1, a2, b1, b2] = coefficients;
                     let y =
                         a0 * self.x0 + a1 * self.x1 + a2 * self.x2 - b1 * self.y1 - b2 * self.y2;
                     *out = y;
@@ -75,16 +103,8 @@ impl<const N: usize> Node<N> for ResonantHighPassFilter {
                 let main_input = &inputs[&self.input_order[0]]; // can panic if there is no id
                 let ref_input = &inputs[&self.input_order[1]]; // can panic if there is no id
 
-                let theta_c =
-                    2.0 * std::f32::consts::PI * ref_input.buffers()[0][0] / self.sr as f32;
-                let d = 1.0 / self.q;
-                let beta = 0.5 * (1.0 - d * theta_c.sin() / 2.0) / (1.0 + d * theta_c.sin() / 2.0);
-                let gama = (0.5 + beta) * theta_c.cos();
-                let a0 = (0.5 + beta + gama) / 2.0;
-                let a1 = -0.5 - beta - gama;
-                let a2 = (0.5 + beta + gama) / 2.0;
-                let b1 = -2.0 * gama;
-                let b2 = 2.0 * beta;
+                let [a0, a1, a2, b1, b2] =
+                    highpass_coefficients(ref_input.buffers()[0][0], self.q, self.sr);
 
                 for (out, x0) in output[0].iter_mut().zip(main_input.buffers()[0].iter()) {
                     let y =
@@ -107,6 +127,16 @@ impl<const N: usize> Node<N> for ResonantHighPassFilter {
                 1 => self.q = value,
                 _ => {}
             },
+            Message::SetSmoothing(pos, smoothing) => match pos {
+                0 => self.cutoff_smooth.set_smoothing(smoothing, self.sr),
+                1 => self.q_smooth.set_smoothing(smoothing, self.sr),
+                _ => {}
+            },
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.cutoff_smooth.set_sr(sr);
+                self.q_smooth.set_sr(sr);
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
 
@@ -116,4 +146,8 @@ impl<const N: usize> Node<N> for ResonantHighPassFilter {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Smoothed, Unit};
 use hashbrown::HashMap;
 #[derive(Debug, Clone)]
 pub struct ResonantLowPassFilter {
@@ -9,6 +9,8 @@ pub struct ResonantLowPassFilter {
     sr: usize,
     cutoff: f32,
     q: f32,
+    cutoff_smooth: Smoothed,
+    q_smooth: Smoothed,
     x0: f32,
     x1: f32,
     x2: f32,
@@ -25,6 +27,15 @@ impl Default for ResonantLowPassFilter {
 }
 
 impl ResonantLowPassFilter {
+    pub const PARAMS: &[ParamInfo] = &[
+        ParamInfo::number("cutoff", 0, 20.0)
+            .unit(Unit::Hz)
+            .range(20., 20000.)
+            .with_ref()
+            .smoothed(),
+        ParamInfo::number("q", 1, 1.0).smoothed(),
+    ];
+
     pub fn new() -> Self {
         Self {
             // events: vec![],
@@ -35,6 +46,8 @@ impl ResonantLowPassFilter {
             step: 0,
             cutoff: 20.,
             q: 1.0,
+            cutoff_smooth: Smoothed::default(),
+            q_smooth: Smoothed::default(),
             x0: 0.,
             x1: 0.,
             x2: 0.,
@@ -97,7 +110,9 @@ impl<const N: usize> Node<N> for ResonantLowPassFilter {
                         }
                     }
 
-                    let theta_c = 2.0 * std::f32::consts::PI * self.cutoff / self.sr as f32;
+                    let cutoff = self.cutoff_smooth.follow(self.cutoff);
+                    let d = 1.0 / self.q_smooth.follow(self.q);
+                    let theta_c = 2.0 * std::f32::consts::PI * cutoff / self.sr as f32;
                     let beta =
                         0.5 * (1.0 - d * theta_c.sin() / 2.0) / (1.0 + d * theta_c.sin() / 2.0);
                     let gama = (0.5 + beta) * theta_c.cos();
@@ -153,11 +168,22 @@ impl<const N: usize> Node<N> for ResonantLowPassFilter {
                 self.pattern = p;
                 self.span = span;
             }
+            Message::SetPosition(pos) => self.step = pos,
             Message::SetToNumber(pos, value) => match pos {
                 0 => self.cutoff = value,
                 1 => self.q = value,
                 _ => {}
             },
+            Message::SetSmoothing(pos, smoothing) => match pos {
+                0 => self.cutoff_smooth.set_smoothing(smoothing, self.sr),
+                1 => self.q_smooth.set_smoothing(smoothing, self.sr),
+                _ => {}
+            },
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.cutoff_smooth.set_sr(sr);
+                self.q_smooth.set_sr(sr);
+            }
             Message::Index(i) => {
                 // println!("got index without order {}", i);
                 self.input_order.push(i)
@@ -172,4 +198,8 @@ impl<const N: usize> Node<N> for ResonantLowPassFilter {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -10,6 +10,10 @@ pub use pass::*;
 #[cfg(feature = "node-sum")]
 mod sum;
 pub use sum::*;
+mod param;
+pub use param::*;
+mod feedback;
+pub use feedback::*;
 
 pub mod oscillator;
 // pub use oscillator::*;
@@ -50,6 +54,18 @@ pub trait Node<const N: usize> {
     fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]);
     fn send_msg(&mut self, info: crate::Message);
 
+    /// Like `send_msg`, for a message meant for `offset` samples into the next block.
+    /// Nodes that can act within a block override this; the rest take it at the block start.
+    fn send_msg_at(&mut self, offset: usize, info: crate::Message) {
+        let _ = offset;
+        self.send_msg(info)
+    }
+
+    /// The parameters the node takes through `send_msg`, if it describes them.
+    fn params(&self) -> &[ParamInfo] {
+        &[]
+    }
+
     fn to_boxed_nodedata(self, channels: usize) -> crate::NodeData<BoxedNodeSend<N>, N>
     where
         Self: Send + 'static + Sized,
@@ -97,6 +113,37 @@ impl<const N: usize> core::fmt::Debug for Input<N> {
     }
 }
 
+/// The ids of a node's inputs, ascending. The map's iteration order is seeded randomly, and
+/// summing floats in a different order can change the last bit of the result.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct InputOrder(Vec<usize>);
+
+impl InputOrder {
+    /// The ids of `inputs`, sorted again only when they changed since the last block.
+    pub(crate) fn follow<const N: usize>(&mut self, inputs: &HashMap<usize, Input<N>>) -> &[usize] {
+        let ids = &mut self.0;
+        if ids.len() != inputs.len() || !ids.iter().all(|id| inputs.contains_key(id)) {
+            ids.clear();
+            ids.extend(inputs.keys());
+            ids.sort_unstable();
+        }
+        ids
+    }
+}
+
+/// The buffer of an input that plays on output `channel`: the input's own channel, and a
+/// mono input on the first two, the way it plays in stereo. Anything past that is silent.
+pub(crate) fn input_channel<const N: usize>(
+    buffers: &[Buffer<N>],
+    channel: usize,
+) -> Option<&Buffer<N>> {
+    match buffers {
+        [mono] if channel < 2 => Some(mono),
+        [_] => None,
+        _ => buffers.get(channel),
+    }
+}
+
 impl<'a, T, const N: usize> Node<N> for &'a mut T
 where
     T: Node<N>,
@@ -107,6 +154,12 @@ where
     fn send_msg(&mut self, info: crate::Message) {
         (**self).send_msg(info)
     }
+    fn send_msg_at(&mut self, offset: usize, info: crate::Message) {
+        (**self).send_msg_at(offset, info)
+    }
+    fn params(&self) -> &[ParamInfo] {
+        (**self).params()
+    }
 }
 
 impl<T, const N: usize> Node<N> for Box<T>
@@ -117,6 +170,9 @@ where
         (**self).process(inputs, output)
     }
     fn send_msg(&mut self, _info: crate::Message) {}
+    fn params(&self) -> &[ParamInfo] {
+        (**self).params()
+    }
 }
 
 impl<const N: usize> Node<N> for dyn Fn(&HashMap<usize, Input<N>>, &mut [Buffer<N>]) {
@@ -1,20 +1,30 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Smoothed};
 use hashbrown::HashMap;
 
 use super::apply_op;
 #[derive(Debug, Clone)]
 pub struct Add {
     val: f32,
+    val_smooth: Smoothed,
+    sr: usize,
     input_order: Vec<usize>,
 }
 
 impl Add {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("value", 0, 0.0).with_ref().smoothed()];
+
     pub fn new(val: f32) -> Self {
         Self {
             val,
+            val_smooth: Smoothed::default(),
+            sr: 44100,
             input_order: Vec::<usize>::new(),
         }
     }
+
+    pub fn sr(self, sr: usize) -> Self {
+        Self { sr, ..self }
+    }
 }
 
 impl From<f32> for Add {
@@ -31,6 +41,7 @@ impl<const N: usize> Node<N> for Add {
             &self.input_order,
             output,
             self.val,
+            &mut self.val_smooth,
             std::ops::Add::add,
         )
     }
@@ -38,6 +49,13 @@ impl<const N: usize> Node<N> for Add {
     fn send_msg(&mut self, info: Message) {
         match info {
             Message::SetToNumber(0, value) => self.val = value,
+            Message::SetSmoothing(0, smoothing) => {
+                self.val_smooth.set_smoothing(smoothing, self.sr)
+            }
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.val_smooth.set_sr(sr);
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -46,4 +64,8 @@ impl<const N: usize> Node<N> for Add {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -3,7 +3,7 @@ pub use mul::Mul;
 mod add;
 pub use add::*;
 
-use crate::{Buffer, Input};
+use crate::{Buffer, Input, Smoothed};
 use hashbrown::HashMap;
 
 fn apply_op<const N: usize>(
@@ -11,6 +11,7 @@ fn apply_op<const N: usize>(
     input_order: &[usize],
     output: &mut [Buffer<N>],
     val: f32,
+    smooth: &mut Smoothed,
     op: impl Fn(f32, f32) -> f32 + Copy,
 ) {
     let (out_left, out_right) = output.split_at_mut(1);
@@ -21,6 +22,7 @@ fn apply_op<const N: usize>(
             for (idx, (out_left, main_in)) in
                 out_left[0].iter_mut().zip(main_input[0].iter()).enumerate()
             {
+                let val = smooth.follow(val);
                 *out_left = op(*main_in, val);
 
                 if let [out_right, ..] = out_right {
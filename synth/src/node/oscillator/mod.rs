@@ -7,7 +7,7 @@ mod tri_osc;
 pub use squ_osc::SquOsc;
 mod squ_osc;
 
-use crate::{Buffer, Input};
+use crate::{Buffer, Input, Smoothed};
 use hashbrown::HashMap;
 
 fn process_oscillation<const N: usize>(
@@ -15,13 +15,14 @@ fn process_oscillation<const N: usize>(
     input_order: &mut [usize],
     output: &mut [Buffer<N>],
     freq: f32,
+    smooth: &mut Smoothed,
     inc: &mut f32,
     mut osc: impl FnMut(&mut f32, f32),
 ) {
     match inputs.len() {
         0 => {
             for out in &mut *output[0] {
-                osc(out, freq);
+                osc(out, smooth.follow(freq));
             }
         }
         1 => {
@@ -1,10 +1,11 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Smoothed, Unit};
 use hashbrown::HashMap;
 #[derive(Debug, Clone)]
 pub struct SinOsc {
     pub freq: f32,
     pub phase: f32,
     pub sr: usize,
+    freq_smooth: Smoothed,
     input_order: Vec<usize>,
 }
 
@@ -14,12 +15,19 @@ impl std::default::Default for SinOsc {
             freq: 1.0,
             phase: 0.0,
             sr: 44100,
+            freq_smooth: Smoothed::default(),
             input_order: vec![],
         }
     }
 }
 
 impl SinOsc {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("freq", 0, 1.0)
+        .unit(Unit::Hz)
+        .range(0., 20000.)
+        .with_ref()
+        .smoothed()];
+
     pub fn new() -> Self {
         Self::default()
     }
@@ -42,7 +50,7 @@ impl<const N: usize> Node<N> for SinOsc {
                     for buf in output.iter_mut() {
                         buf[i] = (self.phase * 2.0 * std::f32::consts::PI).sin();
                     }
-                    self.phase += self.freq / self.sr as f32;
+                    self.phase += self.freq_smooth.follow(self.freq) / self.sr as f32;
                     if self.phase > 1.0 {
                         self.phase -= 1.0
                     }
@@ -71,6 +79,13 @@ impl<const N: usize> Node<N> for SinOsc {
     fn send_msg(&mut self, info: Message) {
         match info {
             Message::SetToNumber(0, value) => self.freq = value,
+            Message::SetSmoothing(0, smoothing) => {
+                self.freq_smooth.set_smoothing(smoothing, self.sr)
+            }
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.freq_smooth.set_sr(sr);
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -79,4 +94,8 @@ impl<const N: usize> Node<N> for SinOsc {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
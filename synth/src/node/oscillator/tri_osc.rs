@@ -1,10 +1,13 @@
-use crate::{oscillator::process_oscillation, Buffer, Input, Message, Node};
+use crate::{
+    oscillator::process_oscillation, Buffer, Input, Message, Node, ParamInfo, Smoothed, Unit,
+};
 use hashbrown::HashMap;
 #[derive(Debug, Clone)]
 pub struct TriOsc {
     pub freq: f32,
     pub phase: f32,
     pub sr: usize,
+    freq_smooth: Smoothed,
     inc: f32,
     input_order: Vec<usize>,
 }
@@ -15,6 +18,7 @@ impl std::default::Default for TriOsc {
             freq: 1.0,
             phase: 0.0,
             sr: 44100,
+            freq_smooth: Smoothed::default(),
             inc: 0.,
             input_order: vec![],
         }
@@ -22,6 +26,12 @@ impl std::default::Default for TriOsc {
 }
 
 impl TriOsc {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("freq", 0, 1.0)
+        .unit(Unit::Hz)
+        .range(0., 20000.)
+        .with_ref()
+        .smoothed()];
+
     pub fn new() -> Self {
         Self::default()
     }
@@ -43,6 +53,7 @@ impl<const N: usize> Node<N> for TriOsc {
             &mut self.input_order,
             output,
             self.freq,
+            &mut self.freq_smooth,
             &mut self.inc,
             |out, freq| {
                 let v = -1.0 + (self.phase * 2.);
@@ -59,6 +70,13 @@ impl<const N: usize> Node<N> for TriOsc {
     fn send_msg(&mut self, info: Message) {
         match info {
             Message::SetToNumber(0, value) => self.freq = value,
+            Message::SetSmoothing(0, smoothing) => {
+                self.freq_smooth.set_smoothing(smoothing, self.sr)
+            }
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.freq_smooth.set_sr(sr);
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -67,4 +85,8 @@ impl<const N: usize> Node<N> for TriOsc {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
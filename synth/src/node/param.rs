@@ -0,0 +1,90 @@
+/// What kind of value a parameter takes, matching the `SetTo*` messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Number,
+    Bool,
+    Symbol,
+    NumberList,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    None,
+    Hz,
+    Seconds,
+    Milliseconds,
+    Samples,
+}
+
+/// Describes a parameter a node takes through `Message::SetTo*(index, ..)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    pub index: u8,
+    pub kind: ParamKind,
+    pub unit: Unit,
+    /// The values that make sense, inclusive. `None` for anything goes.
+    pub range: Option<(f32, f32)>,
+    /// The value the node starts with if not given. `None` for non-numbers.
+    pub default: Option<f32>,
+    /// Whether the parameter can be driven by another chain, like `sin ~mod`.
+    pub accepts_ref: bool,
+    /// Whether the node follows `Message::SetSmoothing` for the parameter.
+    pub smoothed: bool,
+}
+
+impl ParamInfo {
+    pub const fn number(name: &'static str, index: u8, default: f32) -> Self {
+        Self {
+            name,
+            index,
+            kind: ParamKind::Number,
+            unit: Unit::None,
+            range: None,
+            default: Some(default),
+            accepts_ref: false,
+            smoothed: false,
+        }
+    }
+
+    pub const fn symbol(name: &'static str, index: u8) -> Self {
+        Self {
+            kind: ParamKind::Symbol,
+            default: None,
+            ..Self::number(name, index, 0.)
+        }
+    }
+
+    pub const fn list(name: &'static str, index: u8) -> Self {
+        Self {
+            kind: ParamKind::NumberList,
+            default: None,
+            ..Self::number(name, index, 0.)
+        }
+    }
+
+    pub const fn unit(self, unit: Unit) -> Self {
+        Self { unit, ..self }
+    }
+
+    pub const fn range(self, min: f32, max: f32) -> Self {
+        Self {
+            range: Some((min, max)),
+            ..self
+        }
+    }
+
+    pub const fn with_ref(self) -> Self {
+        Self {
+            accepts_ref: true,
+            ..self
+        }
+    }
+
+    pub const fn smoothed(self) -> Self {
+        Self {
+            smoothed: true,
+            ..self
+        }
+    }
+}
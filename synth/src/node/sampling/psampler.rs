@@ -1,10 +1,10 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, SampleBank};
 use hashbrown::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct PSampler {
     playback: Vec<(usize, String, f32)>,
-    samples_dict: HashMap<String, (&'static [f32], usize, usize)>,
+    samples_dict: SampleBank,
     pub events: Vec<(String, f32)>,
     pattern: Vec<(String, f32)>,
     // len: usize,
@@ -18,7 +18,7 @@ pub struct PSampler {
 
 impl PSampler {
     pub fn new(
-        samples_dict: HashMap<String, (&'static [f32], usize, usize)>,
+        samples_dict: SampleBank,
         sr: usize,
         bpm: f32,
         events: Vec<(String, f32)>,
@@ -60,10 +60,10 @@ impl<const N: usize> Node<N> for PSampler {
                 {
                     let pitch = 1.0;
                     let sample_name = &event.0;
-                    let sample = self.samples_dict[sample_name];
-                    let dur = (sample.0.len() / sample.1) as f32
-                        / pitch
-                        / (sample.2 as f32 / self.sr as f32);
+                    let Some(sample) = self.samples_dict.get(sample_name) else {
+                        continue;
+                    };
+                    let dur = sample.frames() as f32 / pitch / (sample.sr as f32 / self.sr as f32);
                     self.playback.push((self.step, sample_name.to_owned(), dur));
                 }
             }
@@ -72,21 +72,23 @@ impl<const N: usize> Node<N> for PSampler {
             for (count, (begin, name, dur)) in self.playback.iter().enumerate() {
                 let pos = (self.step - begin) as f32 / dur;
                 if pos <= 1.0 {
-                    let sample = self.samples_dict[name];
-                    match sample.1 {
+                    let Some(sample) = self.samples_dict.get(name) else {
+                        continue;
+                    };
+                    match sample.channels {
                         1 => {
                             output[0][i] += match pos {
-                                x if x == 0.0 => sample.0[0],
-                                x if x == 1.0 => sample.0[sample.0.len() - 1],
+                                x if x == 0.0 => sample.data[0],
+                                x if x == 1.0 => sample.data[sample.data.len() - 1],
                                 x if x > 0.0 && x < 1.0 => {
-                                    let pos_index_float = x * ((sample.0.len() - 1) as f32);
+                                    let pos_index_float = x * ((sample.data.len() - 1) as f32);
                                     let left = pos_index_float.floor();
                                     let right = pos_index_float.ceil();
                                     let left_portion = pos_index_float - left;
                                     let right_portion = 1. - left_portion;
 
-                                    sample.0[left as usize] * left_portion
-                                        + sample.0[right as usize] * right_portion
+                                    sample.data[left as usize] * left_portion
+                                        + sample.data[right as usize] * right_portion
                                 }
                                 _ => 0.0,
                             };
@@ -95,28 +97,26 @@ impl<const N: usize> Node<N> for PSampler {
                         2 => {
                             match pos {
                                 x if x == 0.0 => {
-                                    output[0][i] += sample.0[0];
-                                    output[1][i] += sample.0[sample.0.len() / sample.1];
+                                    output[0][i] += sample.data[0];
+                                    output[1][i] += sample.data[sample.frames()];
                                 }
                                 x if x == 1.0 => {
-                                    output[0][i] += sample.0[(sample.0.len() / sample.1) - 1];
-                                    output[1][i] += sample.0[sample.0.len() - 1];
+                                    output[0][i] += sample.data[sample.frames() - 1];
+                                    output[1][i] += sample.data[sample.data.len() - 1];
                                 }
                                 x if x > 0.0 && x < 1.0 => {
-                                    let pos_index_float =
-                                        x * (((sample.0.len() / sample.1) - 2) as f32);
+                                    let pos_index_float = x * ((sample.frames() - 2) as f32);
                                     let left = pos_index_float.floor();
                                     let right = pos_index_float.ceil();
                                     let left_portion = pos_index_float - left;
                                     let right_portion = 1. - left_portion;
 
-                                    output[0][i] += sample.0[left as usize] * left_portion
-                                        + sample.0[right as usize] * right_portion;
+                                    output[0][i] += sample.data[left as usize] * left_portion
+                                        + sample.data[right as usize] * right_portion;
 
-                                    output[1][i] += sample.0
-                                        [left as usize + (sample.0.len() / sample.1) + 1]
+                                    output[1][i] += sample.data[left as usize + sample.frames() + 1]
                                         * left_portion
-                                        + sample.0[right as usize + (sample.0.len() / sample.1) + 1]
+                                        + sample.data[right as usize + sample.frames() + 1]
                                             * right_portion
                                 }
                                 _ => {}
@@ -143,6 +143,20 @@ impl<const N: usize> Node<N> for PSampler {
                 self.samples_dict = samples_dict;
                 self.period_in_cycle = span
             }
+            Message::SetPosition(pos) => {
+                self.step = pos;
+                self.playback.clear();
+            }
+            Message::SetBPM(bpm) => self.cycle_dur = 240. / bpm,
+            Message::SetTempo(bpm, pos) => {
+                // samples already playing go on at their own speed
+                for (begin, _, _) in &mut self.playback {
+                    *begin = (*begin + pos).saturating_sub(self.step);
+                }
+                self.step = pos;
+                self.cycle_dur = 240. / bpm;
+            }
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -92,6 +92,27 @@ impl<const N: usize> Node<N> for Arrange {
                     .extend(std::iter::from_fn(|| Some(NumberOrRef::Number(0.0))).take(to_push));
                 self.events.push(NumberOrRef::Number(value));
             }
+            Message::SetPosition(pos) => {
+                // the arrangement loops, so start from the same place in the loop
+                let bars: f32 = self
+                    .events
+                    .iter()
+                    .skip(1)
+                    .step_by(2)
+                    .map(|event| match event {
+                        NumberOrRef::Number(bars) => *bars,
+                        NumberOrRef::Ref(_) => 0.,
+                    })
+                    .sum();
+                let len = (bars * 240.0 / self.bpm * self.sr as f32 / self.speed) as usize;
+                self.step = if len > 0 { pos % len } else { pos };
+            }
+            Message::SetBPM(bpm) => self.bpm = bpm,
+            Message::SetTempo(bpm, pos) => {
+                self.bpm = bpm;
+                <Self as Node<N>>::send_msg(self, Message::SetPosition(pos));
+            }
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo};
 use dasp_signal::{self as signal, Signal};
 use hashbrown::HashMap;
 
@@ -9,6 +9,8 @@ pub struct Choose {
 }
 
 impl Choose {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::list("choices", 0)];
+
     pub fn new(note_list: Vec<f32>, seed: u64) -> Self {
         Self {
             sig: Box::new(signal::noise(seed)),
@@ -30,6 +32,7 @@ impl<const N: usize> Node<N> for Choose {
     fn send_msg(&mut self, info: Message) {
         match info {
             Message::SetToNumberList(0, list) => self.note_list = list,
+            Message::SetSeed(seed) => self.sig = Box::new(signal::noise(seed)),
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -38,4 +41,8 @@ impl<const N: usize> Node<N> for Choose {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
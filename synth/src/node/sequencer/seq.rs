@@ -10,6 +10,8 @@ pub struct Sequencer {
     pub bpm: f32,
     sr: usize,
     pub step: usize,
+    // the sample into the bar played last
+    last: Option<usize>,
     input_order: Vec<usize>,
     // sidechain_lib: HashMap<String, usize>,
 }
@@ -24,6 +26,7 @@ impl Sequencer {
             bpm: 120.,
             sr: 44100,
             step: 0,
+            last: None,
         }
     }
     pub fn ref_order(self, ref_order: HashMap<String, usize>) -> Self {
@@ -37,6 +40,41 @@ impl Sequencer {
     }
 }
 
+// a tempo change rounds the step to a whole sample at the new tempo, which can move it
+// a sample back or skip one
+const ROUNDED: usize = 2;
+
+/// How far the bar moved from `last` to `now`, both in samples into a bar of `len`:
+/// forward, or back when it is a bit behind.
+fn moved(last: usize, now: usize, len: usize) -> Result<usize, usize> {
+    let forward = (now + len - last % len) % len;
+    match forward > len - ROUNDED.min(len) {
+        true => Err(len - forward),
+        false => Ok(forward),
+    }
+}
+
+/// Whether an event `target` samples into the bar plays at `now`, having played `last`.
+/// Right after a tempo change it plays the events passed since `last` and none twice;
+/// anywhere else only the one at `now`.
+fn reaches(last: Option<usize>, now: usize, target: usize, len: usize) -> bool {
+    match last.map(|last| (last, moved(last, now, len))) {
+        Some((last, Ok(forward))) if (1..=ROUNDED).contains(&forward) => {
+            (1..=forward).any(|i| (last + i) % len == target)
+        }
+        Some((_, Ok(0) | Err(_))) => false,
+        _ => target == now,
+    }
+}
+
+/// What `last` is after playing `now`: it stays put when a tempo change moved the bar back.
+fn played(last: Option<usize>, now: usize, len: usize) -> usize {
+    match last.map(|last| (last, moved(last, now, len))) {
+        Some((last, Ok(0) | Err(_))) => last,
+        _ => now,
+    }
+}
+
 impl<const N: usize> Node<N> for Sequencer {
     fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
         // println!("seq inputs info {:?} ; self.input_order {:?}", inputs, self.input_order);
@@ -45,10 +83,13 @@ impl<const N: usize> Node<N> for Sequencer {
                 let bar_length = 240.0 / self.bpm as f64 * self.sr as f64 / self.speed as f64;
                 for out in &mut *output[0] {
                     *out = 0.0;
-                    for event in &self.events {
-                        if (self.step % (bar_length as usize))
-                            == ((event.0 as f64 * bar_length) as usize)
-                        {
+                    // the bar is rarely a whole number of samples, so a truncated
+                    // length would pull the grid further off with every bar
+                    let now = (self.step as f64 % bar_length) as usize;
+                    let len = bar_length.ceil() as usize;
+                    // events from the second compound on fall past the bar and never play
+                    for event in self.events.iter().filter(|event| event.0 < 1.) {
+                        if reaches(self.last, now, (event.0 as f64 * bar_length) as usize, len) {
                             let midi = match event.1 {
                                 UsizeOrRef::Usize(value) => value,
                                 UsizeOrRef::Ref(_) => 0,
@@ -61,6 +102,7 @@ impl<const N: usize> Node<N> for Sequencer {
                             }
                         }
                     }
+                    self.last = Some(played(self.last, now, len));
                     self.step += 1;
                 }
             }
@@ -77,10 +119,13 @@ impl<const N: usize> Node<N> for Sequencer {
                 for (idx, out) in output[0].iter_mut().enumerate() {
                     *out = 0.0;
 
-                    for event in &self.events {
-                        if (self.step % (bar_length as usize))
-                            == ((event.0 as f64 * bar_length) as usize)
-                        {
+                    // the bar is rarely a whole number of samples, so a truncated
+                    // length would pull the grid further off with every bar
+                    let now = (self.step as f64 % bar_length) as usize;
+                    let len = bar_length.ceil() as usize;
+                    // events from the second compound on fall past the bar and never play
+                    for event in self.events.iter().filter(|event| event.0 < 1.) {
+                        if reaches(self.last, now, (event.0 as f64 * bar_length) as usize, len) {
                             let midi = match &event.1 {
                                 UsizeOrRef::Usize(value) => *value as f32,
                                 UsizeOrRef::Ref(s) => {
@@ -97,6 +142,7 @@ impl<const N: usize> Node<N> for Sequencer {
                             }
                         }
                     }
+                    self.last = Some(played(self.last, now, len));
                     self.step += 1;
                 }
             }
@@ -105,10 +151,19 @@ impl<const N: usize> Node<N> for Sequencer {
     fn send_msg(&mut self, info: Message) {
         match info {
             Message::SetBPM(bpm) => self.bpm = bpm,
+            Message::SetTempo(bpm, pos) => {
+                self.bpm = bpm;
+                self.step = pos;
+            }
+            Message::SetPosition(pos) => {
+                self.step = pos;
+                self.last = None;
+            }
             Message::SetToSeq(0, events) => self.events = events,
             Message::SetRefOrder(ref_order) => {
                 self.ref_order = ref_order;
             }
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
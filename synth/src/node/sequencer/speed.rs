@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo};
 use hashbrown::HashMap;
 
 #[derive(Debug, Clone)]
@@ -16,6 +16,10 @@ impl From<f32> for Speed {
     }
 }
 
+impl Speed {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("speed", 0, 1.0)];
+}
+
 impl<const N: usize> Node<N> for Speed {
     fn process(&mut self, _inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
         output[0].silence();
@@ -32,4 +36,8 @@ impl<const N: usize> Node<N> for Speed {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
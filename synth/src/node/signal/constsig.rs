@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo};
 use hashbrown::HashMap;
 #[derive(Debug, Clone)]
 pub struct ConstSig {
@@ -13,6 +13,8 @@ pub struct ConstSig {
 }
 
 impl ConstSig {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("value", 0, 0.0)];
+
     pub fn new(val: f32) -> Self {
         Self {
             val,
@@ -81,9 +83,19 @@ impl<const N: usize> Node<N> for ConstSig {
             }
             Message::SetToNumber(0, value) => self.val = value,
             Message::SetBPM(bpm) => self.bpm = bpm,
+            Message::SetTempo(bpm, pos) => {
+                self.bpm = bpm;
+                self.step = pos;
+            }
+            Message::SetPosition(pos) => self.step = pos,
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
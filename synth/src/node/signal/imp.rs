@@ -1,9 +1,10 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use hashbrown::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct Impulse {
     clock: usize,
+    freq: f32,
     period: usize,
     sr: usize,
     input_order: Vec<usize>,
@@ -16,9 +17,12 @@ impl Default for Impulse {
 }
 
 impl Impulse {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("freq", 0, 1.0).unit(Unit::Hz).with_ref()];
+
     pub fn new() -> Self {
         Self {
             clock: 0,
+            freq: 1.,
             period: 44100,
             sr: 44100,
             input_order: Vec::new(),
@@ -26,7 +30,11 @@ impl Impulse {
     }
     pub fn freq(self, freq: f32) -> Self {
         let period = (self.sr as f32 / freq) as usize;
-        Self { period, ..self }
+        Self {
+            freq,
+            period,
+            ..self
+        }
     }
     pub fn sr(self, sr: usize) -> Self {
         Self { sr, ..self }
@@ -50,10 +58,21 @@ impl<const N: usize> Node<N> for Impulse {
 
     fn send_msg(&mut self, info: Message) {
         match info {
-            Message::SetToNumber(0, value) => self.period = (self.sr as f32 / value) as usize,
+            Message::SetToNumber(0, value) => {
+                self.freq = value;
+                self.period = (self.sr as f32 / value) as usize
+            }
+            Message::SetSampleRate(sr) => {
+                self.sr = sr;
+                self.period = (sr as f32 / self.freq) as usize;
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
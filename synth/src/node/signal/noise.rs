@@ -1,20 +1,31 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo};
 use hashbrown::HashMap;
 
 use dasp_signal::{self as signal, Signal};
 
 pub struct Noise {
     sig: Box<dyn Signal<Frame = f64> + Send>,
+    seed: u64,
+    // set by `Message::SetSeed`, so the same `seed` can sound different in each node
+    node_seed: u64,
     input_order: Vec<usize>,
 }
 
 impl Noise {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::number("seed", 0, 42.0)];
+
     pub fn new(seed: usize) -> Self {
         Self {
             sig: Box::new(signal::noise(seed as u64)),
+            seed: seed as u64,
+            node_seed: 0,
             input_order: Vec::new(),
         }
     }
+
+    fn restart(&mut self) {
+        self.sig = Box::new(signal::noise(self.seed ^ self.node_seed));
+    }
 }
 
 impl<const N: usize> Node<N> for Noise {
@@ -25,10 +36,21 @@ impl<const N: usize> Node<N> for Noise {
     }
     fn send_msg(&mut self, info: Message) {
         match info {
-            Message::SetToNumber(0, value) => self.sig = Box::new(signal::noise(value as u64)),
+            Message::SetToNumber(0, value) => {
+                self.seed = value as u64;
+                self.restart();
+            }
+            Message::SetSeed(seed) => {
+                self.node_seed = seed;
+                self.restart();
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -166,7 +166,15 @@ impl<const N: usize> Node<N> for Points {
                 self.is_looping = b;
                 self.step = 0;
             }
-            Message::SetBPM(bpm) => self.bpm = bpm,
+            Message::SetBPM(bpm) | Message::SetTempo(bpm, _) => self.bpm = bpm,
+            Message::SetPosition(pos) => self.step = pos,
+            Message::SetSampleRate(sr) => {
+                // the points were placed in samples
+                for (time, _) in &mut self.point_list {
+                    *time = (*time as f64 * sr as f64 / self.sr as f64) as usize;
+                }
+                self.sr = sr;
+            }
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             _ => {}
@@ -20,14 +20,17 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 // SOFTWARE.
 
+use super::InputOrder;
 use crate::{Buffer, Input, Message, Node};
 use hashbrown::HashMap;
 
 /// A stateless node that sums each of the inputs onto the output.
 ///
 /// Assumes that the number of buffers per input is equal to the number of output buffers.
-#[derive(Clone, Debug, PartialEq)]
-pub struct Sum;
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sum {
+    order: InputOrder,
+}
 
 /// A stateless node that sums all of the buffers of all of the inputs onto each of the output
 /// buffers.
@@ -42,11 +45,15 @@ pub struct Sum;
 ///
 /// - Summing multiple input channels down to a single output channel.
 /// - Writing a single input channel to multiple output channels.
-#[derive(Clone, Debug, PartialEq)]
-pub struct SumBuffers;
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SumBuffers {
+    order: InputOrder,
+}
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct Sum2;
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sum2 {
+    order: InputOrder,
+}
 
 impl<const N: usize> Node<N> for Sum {
     fn process(&mut self, inputs: &mut HashMap<usize, Input<N>>, output: &mut [Buffer<N>]) {
@@ -55,8 +62,9 @@ impl<const N: usize> Node<N> for Sum {
             out_buffer.silence();
         }
         // Sum the inputs onto the output.
+        let order = self.order.follow(inputs);
         for (channel, out_buffer) in output.iter_mut().enumerate() {
-            for input in inputs.values() {
+            for input in order.iter().map(|id| &inputs[id]) {
                 let in_buffers = input.buffers();
                 if let Some(in_buffer) = in_buffers.get(channel) {
                     dasp_slice::add_in_place(out_buffer, in_buffer);
@@ -75,8 +83,9 @@ impl<const N: usize> Node<N> for Sum2 {
             out_buffer.silence();
         }
         // Sum the inputs onto the output.
+        let order = self.order.follow(inputs);
         for (channel, out_buffer) in output.iter_mut().enumerate() {
-            for input in inputs.values() {
+            for input in order.iter().map(|id| &inputs[id]) {
                 let in_buffers = input.buffers();
                 match in_buffers.get(channel) {
                     Some(in_buffer) => {
@@ -103,7 +112,7 @@ impl<const N: usize> Node<N> for SumBuffers {
         // Fill it with silence.
         out_buffer_first.silence();
         // Sum all input buffers onto the first output buffer.
-        for input in inputs.values() {
+        for input in self.order.follow(inputs).iter().map(|id| &inputs[id]) {
             for in_buffer in input.buffers() {
                 dasp_slice::add_in_place(out_buffer_first, in_buffer);
             }
@@ -1,6 +1,6 @@
 use std::cmp::Ordering;
 
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo, Unit};
 use hashbrown::HashMap;
 
 #[derive(Debug, Clone)]
@@ -10,6 +10,7 @@ pub struct MsgSynth {
     att: f32,
     dec: f32,
     events: Vec<(usize, f32)>, // event.0 is step to play the note, event.1 is midi
+    notes: Vec<(usize, f32)>,  // like events, but from `Message::Note` and played once
     ref_order: HashMap<String, usize>,
     // period_in_cycle: f32, // in cycles, can be 1.2121 for example
     // cycle_dur: f32, // time
@@ -25,11 +26,18 @@ impl Default for MsgSynth {
 }
 
 impl MsgSynth {
+    pub const PARAMS: &[ParamInfo] = &[
+        ParamInfo::number("attack", 1, 0.001).unit(Unit::Seconds),
+        ParamInfo::number("decay", 2, 0.1).unit(Unit::Seconds),
+        ParamInfo::symbol("event", 3),
+    ];
+
     pub fn new() -> Self {
         Self {
             synth_list: vec![],
             phase_list: vec![],
             events: vec![], // test with (88200, 60.)
+            notes: vec![],
             att: 0.001,
             dec: 0.1,
             ref_order: HashMap::new(),
@@ -84,7 +92,7 @@ impl<const N: usize> Node<N> for MsgSynth {
         for out in &mut *output[0] {
             *out = 0.0;
 
-            for event in &self.events {
+            for event in self.events.iter().chain(&self.notes) {
                 if self.step == event.0 {
                     let midi = event.1;
                     let freq = 2f32.powf((midi - 69.) / 12.) * 440.;
@@ -141,6 +149,8 @@ impl<const N: usize> Node<N> for MsgSynth {
             }
             self.step += 1;
         }
+        let step = self.step;
+        self.notes.retain(|note| note.0 >= step);
     }
     fn send_msg(&mut self, info: Message) {
         match info {
@@ -172,9 +182,17 @@ impl<const N: usize> Node<N> for MsgSynth {
                     _ => {}
                 }
             }
+            Message::SetPosition(pos) => {
+                self.step = pos;
+                self.synth_list.clear();
+                self.phase_list.clear();
+                self.notes.clear();
+            }
+            Message::Note(midi) => self.notes.push((self.step, midi)),
             Message::SetRefOrder(ref_order) => {
                 self.ref_order = ref_order;
             }
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -183,4 +201,15 @@ impl<const N: usize> Node<N> for MsgSynth {
             _ => {}
         }
     }
+
+    fn send_msg_at(&mut self, offset: usize, info: Message) {
+        match info {
+            Message::Note(midi) => self.notes.push((self.step + offset, midi)),
+            info => <Self as Node<N>>::send_msg(self, info),
+        }
+    }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
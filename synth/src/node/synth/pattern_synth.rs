@@ -1,4 +1,4 @@
-use crate::{Buffer, Input, Message, Node};
+use crate::{Buffer, Input, Message, Node, ParamInfo};
 use hashbrown::HashMap;
 use std::cmp::Ordering;
 
@@ -18,6 +18,8 @@ pub struct PatternSynth {
 }
 
 impl PatternSynth {
+    pub const PARAMS: &[ParamInfo] = &[ParamInfo::symbol("pattern", 0)];
+
     pub fn new(events: Vec<(f32, f32)>) -> Self {
         Self {
             synth_list: vec![],
@@ -139,19 +141,24 @@ impl<const N: usize> Node<N> for PatternSynth {
                 let pattern = s.replace('`', "");
                 for event in pattern.split(',') {
                     // println!("event {:?}", event);
-                    let mut result = event
-                        .split(' ')
-                        .filter(|x| !x.is_empty())
-                        .map(|x| x.replace(' ', "").parse::<f32>().unwrap());
+                    let mut result = event.split_whitespace().map(str::parse::<f32>);
 
                     // println!("result {:?}", result);
-                    self.events
-                        .push((result.next().unwrap(), result.next().unwrap()));
+                    // events that don't parse are skipped rather than panicking mid-block
+                    if let (Some(Ok(time)), Some(Ok(midi))) = (result.next(), result.next()) {
+                        self.events.push((time, midi));
+                    }
                 }
             }
+            Message::SetPosition(pos) => {
+                self.step = pos;
+                self.synth_list.clear();
+                self.phase_list.clear();
+            }
             Message::SetRefOrder(ref_order) => {
                 self.ref_order = ref_order;
             }
+            Message::SetSampleRate(sr) => self.sr = sr,
             Message::Index(i) => self.input_order.push(i),
             Message::IndexOrder(pos, index) => self.input_order.insert(pos, index),
             Message::ResetOrder => {
@@ -160,4 +167,8 @@ impl<const N: usize> Node<N> for PatternSynth {
             _ => {}
         }
     }
+
+    fn params(&self) -> &[ParamInfo] {
+        Self::PARAMS
+    }
 }
@@ -0,0 +1,82 @@
+use hashbrown::HashMap;
+use std::sync::Arc;
+
+/// Audio data with its layout. Cloning shares the data, so the nodes playing a sample keep
+/// it alive after it is replaced or removed from the [`SampleBank`].
+#[derive(Clone)]
+pub struct Sample {
+    /// The channels one after another, not interleaved.
+    pub data: Arc<[f32]>,
+    pub channels: usize,
+    pub sr: usize,
+}
+
+impl Sample {
+    pub fn new(data: impl Into<Arc<[f32]>>, channels: usize, sr: usize) -> Self {
+        Self {
+            data: data.into(),
+            channels,
+            sr,
+        }
+    }
+
+    /// The length of one channel.
+    pub fn frames(&self) -> usize {
+        self.data.len() / self.channels
+    }
+}
+
+impl std::fmt::Debug for Sample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sample")
+            .field("frames", &self.frames())
+            .field("channels", &self.channels)
+            .field("sr", &self.sr)
+            .finish()
+    }
+}
+
+/// Samples by name, for `sp` and `psampler`.
+#[derive(Debug, Clone, Default)]
+pub struct SampleBank {
+    samples: HashMap<String, Sample>,
+}
+
+impl SampleBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the sample called `name`, returning the one it replaces.
+    pub fn insert(&mut self, name: impl Into<String>, sample: Sample) -> Option<Sample> {
+        self.samples.insert(name.into(), sample)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Sample> {
+        self.samples.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Sample> {
+        self.samples.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.samples.contains_key(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.samples.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
@@ -0,0 +1,97 @@
+/// How a numeric parameter moves to a new value set with `Message::SetToNumber`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Smoothing {
+    /// Jump to the new value.
+    #[default]
+    Off,
+    /// A straight ramp taking this many milliseconds.
+    Linear(f32),
+    /// An exponential approach with this time constant in milliseconds.
+    OnePole(f32),
+}
+
+/// The smoothing state of one parameter. Nodes keep the parameter itself as a plain value
+/// and ask [`Smoothed::follow`] for the value to use at each sample.
+#[derive(Debug, Clone, Default)]
+pub struct Smoothed {
+    smoothing: Smoothing,
+    sr: usize,
+    // the ramp length or time constant in samples
+    len: f32,
+    current: f32,
+    target: f32,
+    step: f32,
+    left: usize,
+    coef: f32,
+    started: bool,
+}
+
+impl Smoothed {
+    pub fn new(smoothing: Smoothing, sr: usize) -> Self {
+        let mut smoothed = Self::default();
+        smoothed.set_smoothing(smoothing, sr);
+        smoothed
+    }
+
+    pub fn set_smoothing(&mut self, smoothing: Smoothing, sr: usize) {
+        self.smoothing = smoothing;
+        self.set_sr(sr);
+    }
+
+    pub fn set_sr(&mut self, sr: usize) {
+        self.sr = sr;
+        self.len = match self.smoothing {
+            Smoothing::Off => 0.,
+            Smoothing::Linear(ms) | Smoothing::OnePole(ms) => ms.max(0.) * sr as f32 / 1000.,
+        };
+        self.coef = (-1. / self.len).exp();
+    }
+
+    pub fn smoothing(&self) -> Smoothing {
+        self.smoothing
+    }
+
+    /// Whether [`Smoothed::follow`] has reached `target`, so nodes can skip it for a block.
+    pub fn is_settled(&self, target: f32) -> bool {
+        self.started && self.current == target && self.target == target
+    }
+
+    /// Moves one sample towards `target`, starting a new ramp whenever it changes.
+    /// The first value followed is taken as is.
+    pub fn follow(&mut self, target: f32) -> f32 {
+        if !self.started {
+            self.started = true;
+            self.jump(target);
+        } else if target != self.target {
+            self.target = target;
+            match self.smoothing {
+                Smoothing::Linear(_) if self.len >= 1. => {
+                    self.left = self.len as usize;
+                    self.step = (target - self.current) / self.left as f32;
+                }
+                Smoothing::OnePole(_) if self.len >= 1. => {}
+                _ => self.current = target,
+            }
+        }
+        if self.current != self.target {
+            self.current = match self.smoothing {
+                Smoothing::Linear(_) if self.left > 1 => {
+                    self.left -= 1;
+                    self.current + self.step
+                }
+                Smoothing::OnePole(_) if (self.target - self.current).abs() > 1e-5 => {
+                    self.target + (self.current - self.target) * self.coef
+                }
+                _ => self.target,
+            };
+        }
+        self.current
+    }
+
+    /// Goes to `value` without a ramp.
+    pub fn jump(&mut self, value: f32) {
+        self.current = value;
+        self.target = value;
+        self.left = 0;
+    }
+}
@@ -4,14 +4,14 @@ use cpal::{
     FromSample, SizedSample,
 };
 use crossterm::event::KeyEvent;
-use glicol::Engine;
+use glicol::{Engine, EngineHandle, EngineProcessor, MasterConfig};
 use ratatui::prelude::Rect;
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
     sync::{
         atomic::{AtomicPtr, AtomicUsize, Ordering},
-        Arc, Mutex,
+        Arc,
     },
     thread,
 };
@@ -31,6 +31,8 @@ const SPECIAL: &str = include_str!("../assets/glicols/SolsticeStream2023.glicol"
 // const SPECIAL: &str = include_str!("../assets/glicols/synth.txt");
 const SAMPLES: &str = include_str!("../.config/sample-list.json");
 const BLOCK_SIZE: usize = 128;
+// how many code changes can be waiting for the audio thread
+const QUEUE_SIZE: usize = 64;
 
 pub struct App {
     config: Config,
@@ -42,7 +44,7 @@ pub struct App {
     last_tick_key_events: Vec<KeyEvent>,
     action_tx: mpsc::UnboundedSender<Action>,
     action_rx: mpsc::UnboundedReceiver<Action>,
-    engine: Arc<Mutex<Engine<BLOCK_SIZE>>>,
+    engine: EngineHandle<BLOCK_SIZE>,
     stream: Option<cpal::Stream>,
     graph_component: GraphComponent<BLOCK_SIZE>,
     log_display: LogDisplay,
@@ -58,43 +60,29 @@ impl App {
     pub fn new(frame_rate: f64) -> Result<Self> {
         let (action_tx, action_rx) = mpsc::unbounded_channel();
 
-        let mut engine = Engine::<BLOCK_SIZE>::new();
+        let host = cpal::default_host();
+        let device = match host.default_output_device() {
+            Some(device) => device,
+            None => {
+                let err_msg = "No output device found";
+                tracing::error!("{err_msg}");
+                return Err(color_eyre::eyre::eyre!(err_msg));
+            }
+        };
+
+        let config = device.default_output_config()?;
+
+        let mut engine = Engine::<BLOCK_SIZE>::with_channels(config.channels() as usize);
+        engine.set_sr(config.sample_rate().0 as usize);
+        engine.set_master(MasterConfig::safe());
+        engine.livecoding = false;
         // match fs::read_to_string("../.config/sample-list.json") {
         // Ok(json_content) => {
         if let Ok(sample_map) = serde_json::from_str::<HashMap<String, String>>(SAMPLES) {
             for (name, path) in sample_map {
-                match hound::WavReader::open(&path) {
-                    Ok(mut reader) => {
-                        let spec = reader.spec();
-                        let samples: Vec<f32> = match spec.sample_format {
-                            hound::SampleFormat::Float => {
-                                reader.samples::<f32>().filter_map(Result::ok).collect()
-                            }
-                            hound::SampleFormat::Int => {
-                                if spec.bits_per_sample == 16 {
-                                    reader
-                                        .samples::<i16>()
-                                        .filter_map(Result::ok)
-                                        .map(|s| s as f32 / 32768.0)
-                                        .collect()
-                                } else {
-                                    error!("Unsupported bits per sample: {}", spec.bits_per_sample);
-                                    continue;
-                                }
-                            }
-                        };
-
-                        // Convert samples to static lifetime - this is safe because the Engine keeps them for its entire lifetime
-                        let samples_static = Box::leak(samples.into_boxed_slice());
-                        engine.add_sample(
-                            &name,
-                            samples_static,
-                            spec.channels as usize,
-                            spec.sample_rate as usize,
-                        );
-                        info!("Loaded sample: {}", name);
-                    }
-                    Err(e) => error!("Failed to read WAV file {}: {}", path, e),
+                match engine.load_sample_file(&name, &path) {
+                    Ok(()) => info!("Loaded sample: {}", name),
+                    Err(e) => error!("{}", e),
                 }
             }
         }
@@ -102,29 +90,14 @@ impl App {
 
         engine
             .update_with_code(r#"out: saw 440.0 >> mul 0.1"#)?;
-        let engine = Arc::new(Mutex::new(engine));
+        let (mut engine, processor) = engine.split(QUEUE_SIZE);
 
         let mut graph_component = GraphComponent::new();
+        graph_component.update_graph(&mut engine);
 
-        if let Ok(engine) = engine.lock() {
-            graph_component.update_node_count(engine.context.graph.node_count());
-        }
-        let host = cpal::default_host();
-        let device = match host.default_output_device() {
-            Some(device) => device,
-            None => {
-                let err_msg = "No output device found";
-                tracing::error!("{err_msg}");
-                return Err(color_eyre::eyre::eyre!(err_msg));
-            }
-        };
-
-        let config = device.default_output_config()?;
-
-        let engine_clone = engine.clone();
         match config.sample_format() {
             cpal::SampleFormat::F32 => {
-                thread::spawn(move || run_audio::<f32>(&device, &config.into(), engine_clone))
+                thread::spawn(move || run_audio::<f32>(&device, &config.into(), processor))
             }
             sample_format => {
                 panic!("Unsupported sample format '{sample_format}'")
@@ -254,30 +227,29 @@ impl App {
                     }
                 }
                 Action::UpdateAudioCode(code) => {
-                    if let Ok(mut engine) = self.engine.lock() {
-                        if engine.update_with_code(&code).is_ok() {
-                            self.graph_component
-                                .update_node_count(engine.context.graph.node_count());
-                        }
+                    if self.engine.update_with_code(&code).is_ok() {
+                        self.graph_component
+                            .update_graph(&mut self.engine);
                     }
                 }
                 Action::SpecialAudio => {
-                    if let Ok(mut engine) = self.engine.lock() {
-                        match engine.update_with_code(SPECIAL) {
-                            Ok(_) => {
-                                engine.set_bpm(640.0);
-                                self
-                                    .graph_component
-                                    .update_node_count(engine.context.graph.node_count());
-                                self
-                                    .graph_component
-                                    .update_bpm(engine.get_bpm());
-                            },
-                            Err(e) => {
-                                let err_msg = format!("Failed to update SPECIAL Glicol code: {e}");
-                                error!("{err_msg}");
-                                self.log_display.add_error(err_msg);
-                            }
+                    match self
+                        .engine
+                        .update_with_code(SPECIAL)
+                        .and_then(|_| self.engine.set_bpm(640.0))
+                    {
+                        Ok(_) => {
+                            self
+                                .graph_component
+                                .update_graph(&mut self.engine);
+                            self
+                                .graph_component
+                                .update_bpm(self.engine.get_bpm());
+                        },
+                        Err(e) => {
+                            let err_msg = format!("Failed to update SPECIAL Glicol code: {e}");
+                            error!("{err_msg}");
+                            self.log_display.add_error(err_msg);
                         }
                     }
                 }
@@ -335,23 +307,14 @@ impl App {
 fn run_audio<T>(
     device: &cpal::Device,
     config: &cpal::StreamConfig,
-    engine: Arc<Mutex<Engine<BLOCK_SIZE>>>,
+    mut processor: EngineProcessor<BLOCK_SIZE>,
 ) -> Result<(), anyhow::Error>
 where
     T: SizedSample + FromSample<f32>,
 {
-    let sr = config.sample_rate.0 as usize;
-    let channels = 2_usize; //config.channels as usize;
-
-    if let Ok(mut engine) = engine.lock() {
-        engine.set_sr(sr);
-        engine.livecoding = false;
-        engine.set_bpm(120.0);
-    }
-
-    let engine_clone = engine.clone();
+    let channels = config.channels as usize;
 
-    let mut prev_block: [glicol_synth::Buffer<BLOCK_SIZE>; 2] = [glicol_synth::Buffer::SILENT; 2];
+    let mut prev_block = vec![glicol_synth::Buffer::<BLOCK_SIZE>::SILENT; channels];
 
     let ptr = prev_block.as_mut_ptr();
     let prev_block_ptr = Arc::new(AtomicPtr::<glicol_synth::Buffer<BLOCK_SIZE>>::new(ptr));
@@ -385,9 +348,8 @@ where
             }
 
             prev_block_pos = BLOCK_SIZE;
-            let mut engine = engine_clone.lock().unwrap();
             while writes < block_step {
-                let block = engine.next_block(vec![]);
+                let block = processor.next_block(&[]);
 
                 if writes + BLOCK_SIZE <= block_step {
                     for i in 0..BLOCK_SIZE {
@@ -1,4 +1,7 @@
+use std::sync::Arc;
+
 use color_eyre::Result;
+use glicol::{EngineHandle, GraphDescription, ParamSetting, ParamValue, Tap};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
@@ -10,7 +13,9 @@ use crate::components::Component;
 
 #[derive(Clone)]
 pub struct GraphComponent<const N: usize> {
-    node_count: usize,
+    graph: GraphDescription,
+    // a level meter for every audible chain
+    meters: Vec<Arc<Tap>>,
     bpm: f32,
     title: String,
 }
@@ -18,14 +23,33 @@ pub struct GraphComponent<const N: usize> {
 impl<const N: usize> GraphComponent<N> {
     pub fn new() -> Self {
         Self {
-            node_count: 0,
+            graph: GraphDescription::default(),
+            meters: vec![],
             bpm: 0.0,
             title: "Glicol Graph".to_string(),
         }
     }
 
-    pub fn update_node_count(&mut self, node_count: usize) {
-        self.node_count = node_count;
+    /// Shows the program the engine runs, with the levels of its audible chains. Chains
+    /// that are still there keep their meter; only new ones are tapped.
+    pub fn update_graph(&mut self, engine: &mut EngineHandle<N>) {
+        self.graph = engine.describe();
+        let audible: Vec<&str> = self
+            .graph
+            .chains
+            .iter()
+            .map(|chain| chain.name.as_str())
+            .filter(|name| !name.contains('~'))
+            .collect();
+        self.meters.retain(|tap| audible.contains(&tap.chain()));
+        for name in audible {
+            if self.meters.iter().any(|tap| tap.chain() == name) {
+                continue;
+            }
+            if let Ok(tap) = engine.tap(name) {
+                self.meters.push(Arc::new(tap));
+            }
+        }
     }
     pub fn update_bpm(&mut self, bpm: f32) {
         self.bpm = bpm;
@@ -55,8 +79,20 @@ impl<const N: usize> Component for GraphComponent<N> {
             .borders(Borders::ALL)
             .style(Style::default());
 
-        let content = if self.node_count > 0 {
-            format!("Graph with {} nodes\nBPM: {}", self.node_count, self.bpm)
+        // an audible chain is connected to the output
+        let content = if !self.graph.edges.is_empty() {
+            let chains: Vec<String> = self
+                .graph
+                .chains
+                .iter()
+                .map(
+                    |chain| match self.meters.iter().find(|tap| tap.chain() == chain.name) {
+                        Some(tap) => format!("{} {}", meter(tap), chain_line(chain)),
+                        None => chain_line(chain),
+                    },
+                )
+                .collect();
+            format!("{}\nBPM: {}", chains.join("\n"), self.bpm)
         } else {
             "No graph loaded".to_string()
         };
@@ -67,3 +103,39 @@ impl<const N: usize> Component for GraphComponent<N> {
         Ok(())
     }
 }
+
+// like `o: sin(freq=~mod) >> mul(value=0.5)`
+fn chain_line(chain: &glicol::ChainDescription) -> String {
+    let nodes: Vec<String> = chain
+        .nodes
+        .iter()
+        .map(|node| {
+            let params: Vec<String> = node
+                .params
+                .iter()
+                .map(|param| {
+                    let value = match &param.value {
+                        ParamSetting::Value(ParamValue::Number(v)) => v.to_string(),
+                        ParamSetting::Value(ParamValue::Bool(v)) => v.to_string(),
+                        ParamSetting::Value(ParamValue::Symbol(v)) => v.clone(),
+                        ParamSetting::Value(ParamValue::NumberList(v)) => format!("{v:?}"),
+                        ParamSetting::Reference(chain) => chain.clone(),
+                        ParamSetting::Pattern => "pattern".to_string(),
+                    };
+                    format!("{}={value}", param.name)
+                })
+                .collect();
+            format!("{}({})", node.name, params.join(", "))
+        })
+        .collect();
+    format!("{}: {}", chain.name, nodes.join(" >> "))
+}
+
+// a VU meter: the RMS level from -60 to 0 dB
+fn meter(tap: &Tap) -> String {
+    const WIDTH: usize = 12;
+    let rms = tap.rms().into_iter().fold(0f32, f32::max);
+    let db = 20. * rms.log10();
+    let filled = (((db + 60.) / 60.).clamp(0., 1.) * WIDTH as f32).round() as usize;
+    format!("[{}{}]", "#".repeat(filled), " ".repeat(WIDTH - filled))
+}